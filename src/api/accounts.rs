@@ -0,0 +1,92 @@
+use goodmorning_bindings::services::v1::{
+    V1All3, V1IdentifierType, V1PasswordId, V1RenameAccount, V1Response, V1SetStatus,
+};
+
+use super::{rejected, GmClient};
+use crate::error::Error as CError;
+
+impl GmClient {
+    /// Creates an account on the instance, returns its ID and token.
+    pub fn create(
+        &self,
+        username: &str,
+        email: &str,
+        password: &str,
+    ) -> Result<(i64, String), CError> {
+        let url = self.url("accounts/v1/create");
+        let body = V1All3 {
+            username: username.to_string(),
+            email: email.to_string(),
+            password: password.to_string(),
+        };
+
+        match self.post(&url, body)? {
+            V1Response::Created { id, token } => Ok((id, token)),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Logs in by username, returns the account's ID and token.
+    pub fn login(&self, username: &str, password: &str) -> Result<(i64, String), CError> {
+        let url = self.url("accounts/v1/login");
+        let body = V1PasswordId {
+            identifier: username.to_string(),
+            identifier_type: V1IdentifierType::Username,
+            password: password.to_string(),
+        };
+
+        match self.post(&url, body)? {
+            V1Response::Login { token, id } => Ok((id, token)),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Replaces the token of the account, returns the new one. Every other session is logged out.
+    pub fn regenerate_token(&self, password: &str) -> Result<String, CError> {
+        let url = self.url("accounts/v1/regeneratetoken");
+        let body = V1PasswordId {
+            identifier: self.id.to_string(),
+            identifier_type: V1IdentifierType::Id,
+            password: password.to_string(),
+        };
+
+        match self.post(&url, body)? {
+            V1Response::RegenerateToken { token } => Ok(token),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    pub fn delete_account(&self) -> Result<(), CError> {
+        let url = self.url("accounts/v1/delete");
+        match self.post(&url, self.token_only())? {
+            V1Response::Deleted => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    pub fn rename(&self, new: &str) -> Result<(), CError> {
+        let url = self.url("accounts/v1/rename");
+        let body = V1RenameAccount {
+            token: self.token(),
+            new: new.to_string(),
+        };
+
+        match self.post(&url, body)? {
+            V1Response::Renamed => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    pub fn set_status(&self, status: &str) -> Result<(), CError> {
+        let url = self.url("accounts/v1/set-status");
+        let body = V1SetStatus {
+            token: self.token(),
+            new: status.to_string(),
+        };
+
+        match self.post(&url, body)? {
+            V1Response::ProfileUpdated => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::any::Any;
+
+use goodmorning_bindings::services::v1::{Compiler, FromFormat, ToFormat, V1Compile, V1Response};
+use goodmorning_bindings::structs::TexCompileRes;
+
+use super::{rejected, GmClient};
+use crate::error::Error as CError;
+
+impl GmClient {
+    /// Compiles a file in the `tex` directory, `path` leaves out `/tex`.
+    /// Returns the job ID and the path of the compiled file.
+    pub fn compile(
+        &self,
+        path: &str,
+        from: FromFormat,
+        to: ToFormat,
+        compiler: Option<Compiler>,
+    ) -> Result<(u64, String), CError> {
+        let url = self.url("compile/v1/simple");
+        let body = V1Compile {
+            path: path.to_string(),
+            token: self.token(),
+            from,
+            to,
+            compiler,
+        };
+
+        match self.post(&url, body)? {
+            V1Response::TexCompiled { id, newpath } => Ok((id, newpath)),
+            V1Response::Any { value } => {
+                let value: Box<dyn Any> = value;
+                match value.downcast::<TexCompileRes>() {
+                    Ok(res) => Ok((res.id, res.newpath)),
+                    Err(_) => Err(CError::unexpected(&url)),
+                }
+            }
+            res => Err(rejected(&url, res)),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use goodmorning_bindings::services::v1::{V1Job, V1Response, V1Unqueue};
+
+use super::{rejected, GmClient};
+use crate::error::Error as CError;
+
+impl GmClient {
+    /// Jobs of the account, the running ones and the queued ones.
+    pub fn jobs(&self) -> Result<(Vec<V1Job>, Vec<V1Job>), CError> {
+        let url = self.url("jobs/v1/jobs");
+        match self.post(&url, self.token_only())? {
+            V1Response::Jobs { current, queue } => Ok((current, queue)),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Removes a job from the queue.
+    pub fn unqueue(&self, id: u64) -> Result<(), CError> {
+        let url = self.url("jobs/v1/unqueue");
+        let body = V1Unqueue {
+            token: self.token(),
+            id,
+        };
+
+        match self.post(&url, body)? {
+            V1Response::Unqueued => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Typed access to the goodmorning endpoints the commands use.
+//!
+//! ```no_run
+//! use gm_cli::api::GmClient;
+//!
+//! let client = GmClient::new("gm.example.com", false).account(1, "token");
+//! for item in client.diritems("/tex")? {
+//!     println!("{}", item.name);
+//! }
+//! # Ok::<(), gm_cli::error::Error>(())
+//! ```
+
+use std::{
+    error::Error,
+    sync::{Arc, RwLock},
+};
+
+use goodmorning_bindings::services::v1::{V1Error, V1Response, V1TokenOnly};
+use log::*;
+use reqwest::{
+    blocking::multipart::{Form, Part},
+    Method, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::error::Error as CError;
+use crate::functions::{deserialize, send, Body};
+
+mod accounts;
+mod compile;
+mod jobs;
+mod profile;
+mod publish;
+mod storage;
+
+/// Asked for a new token after the server rejected the current one in a request to `url`,
+/// the request is sent again if one is returned.
+pub type RenewToken = fn(&GmClient, &str) -> Result<Option<String>, Box<dyn Error>>;
+
+/// An instance, and the account requests are sent as.
+///
+/// Requests go through the shared client, so connection settings from `init_client`
+/// and `instances.yml` apply. A rejected token is returned as an error, unless
+/// `on_invalid_token` sets a way to renew it.
+#[derive(Clone)]
+pub struct GmClient {
+    /// Address of the instance without a scheme, such as `gm.example.com`.
+    pub instance: String,
+    pub id: i64,
+    /// Shared by clones, so a renewed token is used by every one of them.
+    token: Arc<RwLock<String>>,
+    /// Use http instead of https.
+    pub http: bool,
+    renew: Option<RenewToken>,
+}
+
+impl GmClient {
+    /// A client without an account, enough for public endpoints and for logging in.
+    pub fn new(instance: impl Into<String>, http: bool) -> Self {
+        Self {
+            instance: instance.into(),
+            id: 0,
+            token: Arc::default(),
+            http,
+            renew: None,
+        }
+    }
+
+    /// Sends requests as the account with this ID and token.
+    pub fn account(self, id: i64, token: impl Into<String>) -> Self {
+        Self {
+            id,
+            token: Arc::new(RwLock::new(token.into())),
+            ..self
+        }
+    }
+
+    /// Calls `renew` when the server rejects the token, instead of failing with `V1Error::InvalidToken`.
+    pub fn on_invalid_token(self, renew: RenewToken) -> Self {
+        Self {
+            renew: Some(renew),
+            ..self
+        }
+    }
+
+    /// The token requests are sent with, which may have been renewed since the client was made.
+    pub fn token(&self) -> String {
+        self.token.read().unwrap().clone()
+    }
+
+    /// Full url of an endpoint, such as `storage/v1/mkdir`.
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}/api/{endpoint}", self.instance)
+    }
+
+    /// Url of an endpoint that takes the token before a path, such as `storage/v1/file/{token}/a.txt`,
+    /// built for a given token so it can be built again once the token is renewed.
+    fn token_url<'a>(&'a self, endpoint: &'a str, path: &'a str) -> impl Fn(&str) -> String + 'a {
+        move |token| {
+            self.url(&format!(
+                "{endpoint}/{token}/{}",
+                path.trim_start_matches('/')
+            ))
+        }
+    }
+
+    fn get<R: DeserializeOwned>(&self, url: &str) -> Result<R, CError> {
+        let (_, bytes) = self.send(Method::GET, &|_| url.to_string(), Body::Empty)?;
+        Ok(deserialize(url, &bytes)?)
+    }
+
+    fn post<R: DeserializeOwned>(&self, url: &str, body: impl Serialize) -> Result<R, CError> {
+        let body = serde_json::to_value(body).map_err(|e| CError::Other(e.to_string()))?;
+        let (_, bytes) = self.send(Method::POST, &|_| url.to_string(), Body::Json(body))?;
+        Ok(deserialize(url, &bytes)?)
+    }
+
+    /// Sends a request to `url` built for the current token, and once more with a new token
+    /// if the server rejected the token and `on_invalid_token` renewed it.
+    fn send(
+        &self,
+        method: Method,
+        url: &dyn Fn(&str) -> String,
+        mut body: Body,
+    ) -> Result<(StatusCode, Vec<u8>), CError> {
+        let old = self.token();
+        let (status, bytes) = send(method.clone(), &url(&old), &body, self.http)?;
+
+        let renew = match self.renew {
+            Some(renew) if !old.is_empty() && invalid_token(&bytes) => renew,
+            _ => return Ok((status, bytes)),
+        };
+        let new = match renew(self, &url(&old))? {
+            Some(new) => new,
+            None => return Ok((status, bytes)),
+        };
+        *self.token.write().unwrap() = new.clone();
+
+        if let Body::Json(Value::Object(map)) = &mut body {
+            if map.contains_key("token") {
+                map.insert("token".to_string(), Value::String(new.clone()));
+            }
+        }
+
+        info!("Retrying with the new token");
+        Ok(send(method, &url(&new), &body, self.http)?)
+    }
+
+    /// Posts `bytes` as the `file` field of a form.
+    fn post_file(&self, url: &dyn Fn(&str) -> String, bytes: &[u8]) -> Result<V1Response, CError> {
+        let form = || -> Result<Form, Box<dyn Error>> {
+            Ok(Form::new().part(
+                "file",
+                Part::bytes(bytes.to_vec())
+                    .file_name("filename.ext")
+                    .mime_str("application/octet-stream")?,
+            ))
+        };
+
+        info!("Sending request and uploading file");
+        let (_, res) = self.send(Method::POST, url, Body::Form(&form))?;
+        Ok(deserialize(&url(&self.token()), &res)?)
+    }
+
+    fn token_only(&self) -> V1TokenOnly {
+        V1TokenOnly {
+            token: self.token(),
+        }
+    }
+}
+
+fn invalid_token(bytes: &[u8]) -> bool {
+    matches!(
+        serde_json::from_slice(bytes),
+        Ok(V1Response::Error {
+            kind: V1Error::InvalidToken
+        })
+    )
+}
+
+/// The error for a response that isn't the one a request to `url` expects.
+fn rejected(url: &str, res: V1Response) -> CError {
+    match res {
+        V1Response::Error { kind } => CError::server(url, kind),
+        _ => CError::unexpected(url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread::{self, JoinHandle},
+    };
+
+    use super::*;
+
+    static RENEWED: AtomicUsize = AtomicUsize::new(0);
+
+    fn renew(_: &GmClient, _: &str) -> Result<Option<String>, Box<dyn Error>> {
+        RENEWED.fetch_add(1, Ordering::SeqCst);
+        Ok(Some("new".to_string()))
+    }
+
+    /// Answers one connection per response, returns the request line and body of each.
+    fn serve(responses: Vec<V1Response>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let instance = listener.local_addr().unwrap().to_string();
+        let responses = responses
+            .iter()
+            .map(|res| serde_json::to_string(res).unwrap())
+            .collect::<Vec<_>>();
+
+        let server = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|res| {
+                    let (stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream);
+                    let mut request = String::new();
+                    let mut length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line == "\r\n" {
+                            break;
+                        }
+                        if request.is_empty() {
+                            request = line.trim_end().to_string();
+                        }
+                        if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+
+                    write!(
+                        reader.get_mut(),
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{res}",
+                        res.len()
+                    )
+                    .unwrap();
+                    format!("{request} {}", String::from_utf8_lossy(&body))
+                })
+                .collect()
+        });
+        (instance, server)
+    }
+
+    #[test]
+    fn renewed_token_is_kept() {
+        let (instance, server) = serve(vec![
+            V1Response::Error {
+                kind: V1Error::InvalidToken,
+            },
+            V1Response::FileItemCreated,
+            V1Response::DirContent {
+                content: Vec::new(),
+            },
+        ]);
+        let client = GmClient::new(instance, true)
+            .account(1, "old")
+            .on_invalid_token(renew);
+        let copy = client.clone();
+
+        client.mkdir("/a").unwrap();
+        copy.diritems("/a").unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].contains("\"old\""));
+        assert!(requests[1].contains("\"new\""));
+        assert!(requests[2].starts_with("GET /api/storage/v1/diritems/new/a "));
+        assert_eq!(RENEWED.load(Ordering::SeqCst), 1);
+        assert_eq!(client.token(), "new");
+    }
+
+    #[test]
+    fn renewed_url_is_built_again() {
+        fn renew(_: &GmClient, _: &str) -> Result<Option<String>, Box<dyn Error>> {
+            Ok(Some("new".to_string()))
+        }
+
+        let (instance, server) = serve(vec![
+            V1Response::Error {
+                kind: V1Error::InvalidToken,
+            },
+            V1Response::DirContent {
+                content: Vec::new(),
+            },
+        ]);
+        let client = GmClient::new(instance, true)
+            .account(1, "old")
+            .on_invalid_token(renew);
+
+        // the path holds the token too, only the token part may change
+        client.diritems("/old").unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /api/storage/v1/diritems/old/old "));
+        assert!(requests[1].starts_with("GET /api/storage/v1/diritems/new/old "));
+    }
+
+    #[test]
+    fn rejected_token_without_renewal() {
+        let (instance, server) = serve(vec![V1Response::Error {
+            kind: V1Error::InvalidToken,
+        }]);
+        let client = GmClient::new(instance, true).account(1, "old");
+
+        assert!(matches!(
+            client.mkdir("/a"),
+            Err(CError::Server {
+                kind: V1Error::InvalidToken,
+                ..
+            })
+        ));
+        assert_eq!(server.join().unwrap().len(), 1);
+        assert_eq!(client.token(), "old");
+    }
+}
@@ -0,0 +1,73 @@
+use goodmorning_bindings::services::v1::{V1ProfileOnly, V1Response};
+use goodmorning_bindings::structs::{ProfileAccount, ProfileCustomisable};
+
+use super::{rejected, GmClient};
+use crate::error::Error as CError;
+
+impl GmClient {
+    /// Profile and account details of a user on this instance.
+    pub fn profile(&self, username: &str) -> Result<(ProfileCustomisable, ProfileAccount), CError> {
+        self.get_profile(&self.url(&format!("generic/v1/profile/name/{username}")))
+    }
+
+    /// Like `profile`, for the user with this ID.
+    pub fn profile_by_id(&self, id: i64) -> Result<(ProfileCustomisable, ProfileAccount), CError> {
+        self.get_profile(&self.url(&format!("generic/v1/profile/id/{id}")))
+    }
+
+    fn get_profile(&self, url: &str) -> Result<(ProfileCustomisable, ProfileAccount), CError> {
+        match self.get(url)? {
+            V1Response::Profile { profile, account } => Ok((profile, account)),
+            res => Err(rejected(url, res)),
+        }
+    }
+
+    /// The editable part of the account's profile.
+    pub fn profile_only(&self) -> Result<ProfileCustomisable, CError> {
+        let url = self.url(&format!("generic/v1/profile-only/id/{}", self.id));
+
+        match self.get(&url)? {
+            V1Response::ProfileOnly { profile } => Ok(profile),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    pub fn set_profile(&self, profile: ProfileCustomisable) -> Result<(), CError> {
+        let url = self.url("generic/v1/set-profile");
+        let body = V1ProfileOnly {
+            profile,
+            token: self.token(),
+        };
+
+        match self.post(&url, body)? {
+            V1Response::ProfileUpdated => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    pub fn reset_profile(&self) -> Result<(), CError> {
+        let url = self.url("generic/v1/reset-profile");
+        match self.post(&url, self.token_only())? {
+            V1Response::ProfileUpdated => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Uploads a new profile image.
+    pub fn set_pfp(&self, bytes: &[u8]) -> Result<(), CError> {
+        let url = |token: &str| self.url(&format!("generic/v1/set-pfp/{token}"));
+        match self.post_file(&url, bytes)? {
+            V1Response::ProfileUpdated => Ok(()),
+            res => Err(rejected(&url(&self.token()), res)),
+        }
+    }
+
+    /// Goes back to the default profile image.
+    pub fn reset_pfp(&self) -> Result<(), CError> {
+        let url = self.url("generic/v1/reset-pfp");
+        match self.post(&url, self.token_only())? {
+            V1Response::PfpReset => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+}
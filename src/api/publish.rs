@@ -0,0 +1,39 @@
+use goodmorning_bindings::services::v1::{V1Publish, V1Response, V1TexUserPublish};
+
+use super::{rejected, GmClient};
+use crate::error::Error as CError;
+
+impl GmClient {
+    /// Publishes a file in the `tex` directory, `path` leaves out `/tex`. Returns the ID of the published item.
+    pub fn publish(&self, path: &str, title: &str, desc: &str) -> Result<u64, CError> {
+        let url = self.url("publish/v1/publish");
+        let body = V1Publish {
+            token: self.token(),
+            path: path.to_string(),
+            title: title.to_string(),
+            desc: desc.to_string(),
+        };
+
+        match self.post(&url, body)? {
+            V1Response::TexPublished { id } => Ok(id),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// A page of the items a user published, `page` starts at 1.
+    pub fn publishes(
+        &self,
+        username: &str,
+        page: u64,
+        per_page: u64,
+    ) -> Result<Vec<V1TexUserPublish>, CError> {
+        let url = self.url(&format!(
+            "publish/v1/publishes/name/{username}?page={page}&page_size={per_page}"
+        ));
+
+        match self.get(&url)? {
+            V1Response::TexUserPublishes { items } => Ok(items),
+            res => Err(rejected(&url, res)),
+        }
+    }
+}
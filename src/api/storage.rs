@@ -0,0 +1,155 @@
+use goodmorning_bindings::services::v1::{
+    ItemVisibility, V1DirItem, V1FromTo, V1PathOnly, V1PathVisibility, V1Response,
+};
+use log::*;
+use reqwest::Method;
+
+use super::{rejected, GmClient};
+use crate::error::Error as CError;
+use crate::functions::{deserialize, Body};
+
+impl GmClient {
+    /// Items in a directory, in the order the server lists them.
+    pub fn diritems(&self, path: &str) -> Result<Vec<V1DirItem>, CError> {
+        let url = self.token_url("storage/v1/diritems", path);
+        let (_, bytes) = self.send(Method::GET, &url, Body::Empty)?;
+
+        let url = url(&self.token());
+        match deserialize(&url, &bytes)? {
+            V1Response::DirContent { content } => Ok(content),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Content of a file.
+    pub fn file(&self, path: &str) -> Result<Vec<u8>, CError> {
+        let url = self.token_url("storage/v1/file", path);
+        let (status, bytes) = self.send(Method::GET, &url, Body::Empty)?;
+
+        let url = url(&self.token());
+        if !status.is_success() {
+            error!("Server responded with code `{}`", status.as_u16());
+            return Err(rejected(&url, deserialize(&url, &bytes)?));
+        }
+
+        Ok(bytes)
+    }
+
+    /// Uploads a file to `path`, replacing what is there with `overwrite`.
+    pub fn upload(&self, path: &str, bytes: &[u8], overwrite: bool) -> Result<(), CError> {
+        let url = self.token_url(
+            if overwrite {
+                "storage/v1/upload-overwrite"
+            } else {
+                "storage/v1/upload"
+            },
+            path,
+        );
+        match self.post_file(&url, bytes)? {
+            V1Response::FileItemCreated => Ok(()),
+            res => Err(rejected(&url(&self.token()), res)),
+        }
+    }
+
+    pub fn mkdir(&self, path: &str) -> Result<(), CError> {
+        self.create_item("storage/v1/mkdir", path)
+    }
+
+    /// Creates an empty file.
+    pub fn touch(&self, path: &str) -> Result<(), CError> {
+        self.create_item("storage/v1/touch", path)
+    }
+
+    fn create_item(&self, endpoint: &str, path: &str) -> Result<(), CError> {
+        let url = self.url(endpoint);
+        match self.post(&url, self.path_only(path))? {
+            V1Response::FileItemCreated => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Deletes a file or a directory with everything in it.
+    pub fn delete(&self, path: &str) -> Result<(), CError> {
+        let url = self.url("storage/v1/delete");
+        match self.post(&url, self.path_only(path))? {
+            V1Response::Deleted => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Copies `from` in the storage of user `from_user` to `to` in this account's storage.
+    pub fn copy(
+        &self,
+        from: &str,
+        to: &str,
+        from_user: i64,
+        overwrite: bool,
+    ) -> Result<(), CError> {
+        let endpoint = if overwrite { "copy-overwrite" } else { "copy" };
+        let url = self.url(&format!("storage/v1/{endpoint}"));
+
+        match self.post(&url, self.transfer_body(from, to, from_user))? {
+            V1Response::Copied => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Moves `from` in the storage of user `from_user` to `to` in this account's storage.
+    pub fn move_item(
+        &self,
+        from: &str,
+        to: &str,
+        from_user: i64,
+        overwrite: bool,
+    ) -> Result<(), CError> {
+        let endpoint = if overwrite { "move-overwrite" } else { "move" };
+        let url = self.url(&format!("storage/v1/{endpoint}"));
+
+        match self.post(&url, self.transfer_body(from, to, from_user))? {
+            V1Response::Moved => Ok(()),
+            res => Err(rejected(&url, res)),
+        }
+    }
+
+    /// Sets the visibility of an item, returns whether it changed.
+    pub fn set_visibility(&self, path: &str, visibility: ItemVisibility) -> Result<bool, CError> {
+        let url = self.url("storage/v1/set-visibility");
+        let body = V1PathVisibility {
+            path: path.to_string(),
+            visibility,
+            token: self.token(),
+        };
+
+        visibility_changed(&url, self.post(&url, body)?)
+    }
+
+    /// Makes an item inherit the visibility of its directory, returns whether it changed.
+    pub fn remove_visibility(&self, path: &str) -> Result<bool, CError> {
+        let url = self.url("storage/v1/remove-visibility");
+        visibility_changed(&url, self.post(&url, self.path_only(path))?)
+    }
+
+    fn path_only(&self, path: &str) -> V1PathOnly {
+        V1PathOnly {
+            path: path.to_string(),
+            token: self.token(),
+        }
+    }
+
+    fn transfer_body(&self, from: &str, to: &str, from_user: i64) -> V1FromTo {
+        V1FromTo {
+            from: from.to_string(),
+            to: to.to_string(),
+            from_userid: from_user,
+            token: self.token(),
+        }
+    }
+}
+
+fn visibility_changed(url: &str, res: V1Response) -> Result<bool, CError> {
+    match res {
+        V1Response::VisibilityChanged => Ok(true),
+        V1Response::NothingChanged => Ok(false),
+        res => Err(rejected(url, res)),
+    }
+}
@@ -0,0 +1,489 @@
+use std::{collections::HashMap, error::Error, str::FromStr};
+
+use log::*;
+
+use crate::api::GmClient;
+use crate::config::AccountConfig;
+use crate::error::Error as CError;
+use crate::functions::{
+    prompt, prompt_password, relogin, require_input, CONNECT_TIMEOUT, RETRIES, TIMEOUT,
+};
+use crate::logs::{KEEP, LEVELS};
+use crate::output::FORMATS;
+use crate::traits::types::CommandFnType;
+
+/// Flags accepted by every command.
+pub const GLOBAL: &[Arg] = &[
+    Arg::opt("account").help("Run as another saved account"),
+    Arg::opt("instance").help("Instance to send requests to"),
+    Arg::opt("id").help("Account ID to use"),
+    Arg::opt("token").help("Token to use"),
+    Arg::flag("http").help("Use http instead of https"),
+    Arg::flag("no-http").help("Use https, even if `GM_HTTP` or a default says otherwise"),
+    Arg::flag("yes").help("Skip confirmations"),
+    Arg::flag("no-input").help("Never prompt, fail when a value is missing"),
+    Arg::opt("output")
+        .kind(Kind::Choice(FORMATS))
+        .help("Print the result as text, or as json or yaml for scripts"),
+    Arg::opt("ca-file")
+        .kind(Kind::File)
+        .help("PEM file with extra root certificates"),
+    Arg::opt("client-cert")
+        .kind(Kind::File)
+        .help("PEM client certificate for mutual TLS"),
+    Arg::opt("client-key")
+        .kind(Kind::File)
+        .help("PKCS#8 key for the client certificate"),
+    Arg::opt("proxy").help("Send requests through this proxy"),
+    Arg::flag("no-proxy").help("Ignore proxies set in the environment"),
+    Arg::flag("insecure-skip-verify").help("Accept invalid certificates"),
+    Arg::opt("connect-timeout")
+        .kind(Kind::Uint)
+        .default(CONNECT_TIMEOUT)
+        .help("Seconds to wait for a connection, 0 waits forever"),
+    Arg::opt("timeout").kind(Kind::Uint).default(TIMEOUT).help(
+        "Seconds to wait for a whole request, including its upload or download, 0 waits forever",
+    ),
+    Arg::opt("max-retries")
+        .kind(Kind::Uint)
+        .default(RETRIES)
+        .help("Times to resend a request that failed for a transient reason"),
+    Arg::flag("help").help("Show help for the command instead of running it"),
+    Arg::flag("quiet").short('q').help("Only print errors"),
+    Arg::flag("verbose")
+        .short('v')
+        .kind(Kind::Count)
+        .help("Print debug logs, or trace logs when given twice"),
+    Arg::opt("log-level")
+        .kind(Kind::Choice(LEVELS))
+        .help("Print logs down to this level, overrides `--quiet` and `--verbose`"),
+    Arg::opt("keep-logs")
+        .kind(Kind::Uint)
+        .default(KEEP)
+        .help("How many run logs to keep, counting this run"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Str,
+    Int,
+    /// A whole number that can't be negative, such as a count or an ID.
+    Uint,
+    /// A flag without a value.
+    Switch,
+    /// A flag without a value that may be repeated, such as `-vv`, its value is the count.
+    Count,
+    /// Like `Str`, but prompted for without echoing.
+    Secret,
+    Choice(&'static [&'static str]),
+    /// A path in your storage, completed from the server.
+    Path,
+    /// A file on this device.
+    File,
+}
+
+impl Kind {
+    pub fn takes_value(self) -> bool {
+        !matches!(self, Self::Switch | Self::Count)
+    }
+}
+
+/// One positional argument, flag or option of a command.
+#[derive(Clone, Copy)]
+pub struct Arg {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub positional: bool,
+    /// One letter name such as `-q`, only for flags without a value.
+    pub short: Option<char>,
+    pub required: bool,
+    /// Asked for when missing, arguments without one are an error instead.
+    pub prompt: Option<&'static str>,
+    pub default: Option<&'static str>,
+    pub help: &'static str,
+}
+
+/// Everything the dispatcher needs to know to parse, check and run a command.
+pub struct Spec {
+    pub about: &'static str,
+    pub args: &'static [Arg],
+    /// Refuse to run without a saved or given account.
+    pub login: bool,
+    /// Full command lines without the executable name, shown in help.
+    pub examples: &'static [&'static str],
+    /// Paths of commands worth looking at next, such as `mv` or `config set`.
+    pub related: &'static [&'static str],
+    pub run: CommandFnType,
+}
+
+/// Whether `--flag` is a global option followed by its value, command flags are not known here.
+pub fn global_takes_value(flag: &str) -> bool {
+    !flag.contains('=')
+        && GLOBAL
+            .iter()
+            .any(|global| global.name == flag && global.kind.takes_value())
+}
+
+/// Whether a word is one or more short flags, such as `-q` or `-vv`.
+pub fn is_short(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next() == Some('-') && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+}
+
+/// Value of a global option in a command line before it is parsed, as `--name value` or `--name=value`.
+pub fn raw_option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        match arg.strip_prefix("--")?.strip_prefix(name)? {
+            "" => args.get(i + 1).map(String::as_str),
+            rest => rest.strip_prefix('='),
+        }
+    })
+}
+
+/// How many times a global flag is given in a command line before it is parsed, counting `-vv` as two.
+pub fn raw_count(args: &[String], arg: &Arg) -> usize {
+    args.iter()
+        .map(|word| match word.strip_prefix("--") {
+            Some(flag) => usize::from(flag == arg.name),
+            None if is_short(word) => word.chars().filter(|c| Some(*c) == arg.short).count(),
+            None => 0,
+        })
+        .sum()
+}
+
+/// Checked arguments handed to a command, along with config values such as `instance`.
+///
+/// Reading a name the command's spec doesn't declare panics in debug builds, so a misspelled
+/// name fails in testing instead of reading as unset.
+pub struct Args {
+    spec: &'static Spec,
+    values: HashMap<String, String>,
+}
+
+/// Names set for commands without being declared, such as the directory of the `fs` repl.
+const CONTEXT: &[&str] = &["prefix"];
+
+impl Arg {
+    /// A required positional argument, also accepted as `--name value`.
+    pub const fn pos(name: &'static str) -> Self {
+        Self {
+            name,
+            kind: Kind::Str,
+            positional: true,
+            short: None,
+            required: true,
+            prompt: None,
+            default: None,
+            help: "",
+        }
+    }
+
+    /// An optional `--name value` option.
+    pub const fn opt(name: &'static str) -> Self {
+        Self {
+            positional: false,
+            required: false,
+            ..Self::pos(name)
+        }
+    }
+
+    /// An optional `--name` flag without a value.
+    pub const fn flag(name: &'static str) -> Self {
+        Self {
+            kind: Kind::Switch,
+            ..Self::opt(name)
+        }
+    }
+
+    pub const fn short(self, short: char) -> Self {
+        Self {
+            short: Some(short),
+            ..self
+        }
+    }
+
+    pub const fn kind(self, kind: Kind) -> Self {
+        Self { kind, ..self }
+    }
+
+    pub const fn optional(self) -> Self {
+        Self {
+            required: false,
+            ..self
+        }
+    }
+
+    pub const fn prompt(self, prompt: &'static str) -> Self {
+        Self {
+            prompt: Some(prompt),
+            ..self
+        }
+    }
+
+    pub const fn default(self, default: &'static str) -> Self {
+        Self {
+            default: Some(default),
+            ..self
+        }
+    }
+
+    pub const fn help(self, help: &'static str) -> Self {
+        Self { help, ..self }
+    }
+
+    /// Shown in usage lines, such as `[path]`, `(page)` or `(--overwrite)`.
+    pub fn usage(&self) -> String {
+        match (self.positional, self.required, self.kind) {
+            (true, true, _) => format!("[{}]", self.name),
+            (true, false, _) => format!("({})", self.name),
+            (false, _, kind) if !kind.takes_value() => format!("(--{})", self.name),
+            (false, _, _) => format!("(--{} <{}>)", self.name, self.name),
+        }
+    }
+
+    fn check(&self, value: &str) -> Result<(), Box<dyn Error>> {
+        match self.kind {
+            Kind::Int if value.parse::<i64>().is_err() => Err(CError::Usage(format!(
+                "`{}` must be a number, got `{value}`",
+                self.name
+            ))
+            .into()),
+            Kind::Uint if value.parse::<u64>().is_err() => Err(CError::Usage(format!(
+                "`{}` must be a whole number of 0 or more, got `{value}`",
+                self.name
+            ))
+            .into()),
+            Kind::Choice(choices) if !choices.contains(&value) => Err(CError::Usage(format!(
+                "`{}` must be one of {}, got `{value}`",
+                self.name,
+                choices
+                    .iter()
+                    .map(|choice| format!("`{choice}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Spec {
+    pub fn find(&self, name: &str) -> Option<&Arg> {
+        self.args.iter().chain(GLOBAL).find(|arg| arg.name == name)
+    }
+
+    pub fn find_short(&self, short: char) -> Option<&Arg> {
+        self.args
+            .iter()
+            .chain(GLOBAL)
+            .find(|arg| arg.short == Some(short))
+    }
+
+    pub fn positional(&self) -> impl Iterator<Item = &Arg> {
+        self.args.iter().filter(|arg| arg.positional)
+    }
+
+    /// Arguments of this command for a usage line, such as `[from] [to] (--overwrite)`.
+    pub fn usage(&self) -> String {
+        self.args
+            .iter()
+            .map(Arg::usage)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Fills in positional arguments, defaults and prompts, then checks every value.
+    pub fn prepare(
+        &self,
+        map: &mut HashMap<String, String>,
+        positional: Vec<String>,
+    ) -> Result<(), Box<dyn Error>> {
+        let slots = self.positional().collect::<Vec<_>>();
+        if positional.len() > slots.len() {
+            return Err(CError::Usage("Too many arguments".to_string()).into());
+        }
+        slots.iter().zip(positional).for_each(|(arg, value)| {
+            map.insert(arg.name.to_string(), value);
+        });
+
+        for arg in GLOBAL.iter().filter(|arg| arg.kind.takes_value()) {
+            if let Some(value) = map.get(arg.name) {
+                arg.check(value)?;
+            }
+        }
+
+        if self.login && !AccountConfig::is_loggedin_map(map) {
+            error!("You are not logged in");
+            return Err(CError::NotLoggedIn.into());
+        }
+
+        for arg in self.args {
+            if !map.contains_key(arg.name) {
+                let value = match (arg.default, arg.required, arg.prompt) {
+                    (Some(default), _, _) => default.to_string(),
+                    (None, false, _) => continue,
+                    (None, true, Some(msg)) => {
+                        require_input(arg.name)?;
+                        match arg.kind {
+                            Kind::Secret => prompt_password(msg)?,
+                            _ => prompt(msg)?,
+                        }
+                    }
+                    (None, true, None) => {
+                        return Err(CError::Usage(format!("missing argument `{}`", arg.name)).into())
+                    }
+                };
+                map.insert(arg.name.to_string(), value);
+            }
+
+            if arg.kind.takes_value() {
+                arg.check(map.get(arg.name).unwrap())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Args {
+    pub fn new(spec: &'static Spec, values: HashMap<String, String>) -> Self {
+        Self { spec, values }
+    }
+
+    /// Value of an argument, empty if it is not set.
+    pub fn str(&self, name: &str) -> &str {
+        self.opt(name).unwrap_or_default()
+    }
+
+    /// Value of an argument, `None` if it is not set.
+    pub fn opt(&self, name: &str) -> Option<&str> {
+        self.declared(name);
+        self.values.get(name).map(String::as_str)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.declared(name);
+        self.values.contains_key(name)
+    }
+
+    /// Sets an argument, such as one that was prompted for.
+    pub fn set(&mut self, name: &str, value: String) {
+        self.declared(name);
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Whether an instance, ID and token are all set.
+    pub fn logged_in(&self) -> bool {
+        AccountConfig::is_loggedin_map(&self.values)
+    }
+
+    /// A client for the instance and account in the arguments, asking to log in again if the token is rejected.
+    pub fn client(&self) -> Result<GmClient, CError> {
+        Ok(self.account_client()?.on_invalid_token(relogin))
+    }
+
+    /// A client for the instance and account in the arguments, failing if the token is rejected.
+    pub fn account_client(&self) -> Result<GmClient, CError> {
+        let id = match self.str("id") {
+            "" => 0,
+            id => id
+                .parse()
+                .map_err(|_| CError::Usage(format!("`id` must be a number, got `{id}`")))?,
+        };
+        Ok(GmClient::new(self.str("instance"), self.flag("http")).account(id, self.str("token")))
+    }
+
+    /// Value of a `Kind::Int` argument, an error if it is missing.
+    pub fn int(&self, name: &str) -> Result<i64, CError> {
+        self.number(name)
+    }
+
+    /// Value of a `Kind::Uint` argument, an error if it is missing.
+    pub fn uint(&self, name: &str) -> Result<u64, CError> {
+        self.number(name)
+    }
+
+    fn number<T: FromStr>(&self, name: &str) -> Result<T, CError> {
+        let value = self
+            .opt(name)
+            .ok_or_else(|| CError::Usage(format!("missing argument `{name}`")))?;
+        value
+            .parse()
+            .map_err(|_| CError::Usage(format!("`{name}` must be a number, got `{value}`")))
+    }
+
+    pub fn into_map(self) -> HashMap<String, String> {
+        self.values
+    }
+
+    fn declared(&self, name: &str) {
+        debug_assert!(
+            self.spec.find(name).is_some() || CONTEXT.contains(&name),
+            "`{name}` is not an argument of this command"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Output;
+
+    #[test]
+    fn uint_rejects_negative_values() {
+        let arg = Arg::opt("retries").kind(Kind::Uint);
+        assert!(arg.check("3").is_ok());
+        assert!(arg.check("0").is_ok());
+        assert!(arg.check("-1").is_err());
+        assert!(arg.check("three").is_err());
+
+        let arg = Arg::opt("run").kind(Kind::Int);
+        assert!(arg.check("-1").is_ok());
+    }
+
+    const SPEC: Spec = Spec {
+        about: "",
+        args: &[
+            Arg::opt("page").kind(Kind::Uint),
+            Arg::opt("user").kind(Kind::Int),
+            Arg::opt("missing"),
+        ],
+        login: false,
+        examples: &[],
+        related: &[],
+        run: |_| Ok(Output::new("")),
+    };
+
+    #[test]
+    fn typed_accessors() {
+        let args = Args::new(
+            &SPEC,
+            HashMap::from([
+                ("page".to_string(), "2".to_string()),
+                ("user".to_string(), "-4".to_string()),
+            ]),
+        );
+
+        assert_eq!(args.uint("page").unwrap(), 2);
+        assert_eq!(args.int("user").unwrap(), -4);
+        assert!(matches!(args.uint("user"), Err(CError::Usage(_))));
+        assert!(matches!(args.uint("missing"), Err(CError::Usage(_))));
+        assert_eq!(args.opt("missing"), None);
+        assert_eq!(args.str("missing"), "");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "`pgae` is not an argument of this command")]
+    fn undeclared_names_panic() {
+        Args::new(&SPEC, HashMap::new()).str("pgae");
+    }
+
+    #[test]
+    fn globals_and_context_are_declared() {
+        let args = Args::new(&SPEC, HashMap::new());
+        assert!(!args.flag("yes"));
+        assert_eq!(args.str("prefix"), "");
+    }
+}
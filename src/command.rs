@@ -1,15 +1,40 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, error::Error, fmt::Display};
 
-use crate::traits::types::CommandFnType;
+use crate::args::{global_takes_value, is_short, Arg, Args, Kind, Spec, GLOBAL};
+use crate::commands::aliases;
+use crate::error::{exit_code, Error as CError, EXIT_USAGE};
+use crate::functions::{interactive, prompt, suggest};
+use crate::help;
+use crate::output::{self, Format, Output};
 use log::*;
 
 pub enum Command {
-    Command(CommandFnType),
+    Command(&'static Spec),
     Category(HashMap<&'static str, Command>),
 }
 
-impl From<CommandFnType> for Command {
-    fn from(value: CommandFnType) -> Self {
+/// Command line split into the command it names, its flags and its positional arguments.
+pub struct Parsed {
+    pub path: Vec<&'static str>,
+    pub spec: &'static Spec,
+    pub flags: HashMap<String, String>,
+    pub positional: Vec<String>,
+}
+
+/// A command or flag that doesn't exist, along with the closest ones that do.
+#[derive(Debug)]
+pub struct Unknown {
+    message: String,
+    pub suggestions: Vec<String>,
+    /// Command path the name was looked up in, for pointing at `help`.
+    pub path: Vec<&'static str>,
+    /// The command line with aliases expanded, and where the unknown name is in it.
+    args: Vec<String>,
+    index: usize,
+}
+
+impl From<&'static Spec> for Command {
+    fn from(value: &'static Spec) -> Self {
         Self::Command(value)
     }
 }
@@ -20,8 +45,8 @@ impl From<HashMap<&'static str, Command>> for Command {
     }
 }
 
-impl From<HashMap<&'static str, CommandFnType>> for Command {
-    fn from(value: HashMap<&'static str, CommandFnType>) -> Self {
+impl From<HashMap<&'static str, &'static Spec>> for Command {
+    fn from(value: HashMap<&'static str, &'static Spec>) -> Self {
         let mut map = HashMap::new();
 
         value.into_iter().for_each(|(name, f)| {
@@ -34,19 +59,217 @@ impl From<HashMap<&'static str, CommandFnType>> for Command {
 
 impl Command {
     pub fn run(&self, map: HashMap<String, String>, args: Vec<String>) {
-        match self {
-            Self::Command(command) => match command(map, args) {
-                Ok(msg) => debug!("Command finished with message `{msg}`"),
-                Err(e) => error!("Command exited with error `{e}`"),
-            },
-            Self::Category(cog) if args.is_empty() || !cog.contains_key(args[0].as_str()) => {
-                error!("No such command")
+        if args.iter().any(|arg| arg == "--help") {
+            println!("{}", help::help(self, &args));
+            return;
+        }
+
+        if let Some(parsed) = self.parse_or_suggest(&args) {
+            // already logged by `Parsed::run`
+            let _ = parsed.run(map);
+        }
+    }
+
+    /// Like `parse`, but errors are logged along with suggestions for unknown names.
+    /// When interactive, the closest suggestion is offered to run instead.
+    pub fn parse_or_suggest(&self, args: &[String]) -> Option<Parsed> {
+        let e = match self.parse(args) {
+            Ok(parsed) => return Some(parsed),
+            Err(e) => e,
+        };
+        error!("{e}");
+        output::render_error(&*e, EXIT_USAGE);
+
+        let unknown = e.downcast_ref::<Unknown>()?;
+        if !unknown.suggestions.is_empty() {
+            info!(
+                "Did you mean {}?",
+                unknown
+                    .suggestions
+                    .iter()
+                    .map(|suggestion| format!("`{suggestion}`"))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            );
+        }
+        info!(
+            "Run `{}` to see what is available",
+            [env!("CARGO_PKG_NAME"), "help"]
+                .iter()
+                .chain(&unknown.path)
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
+        let suggestion = unknown.suggestions.first()?;
+        // a rerun would print a second document after the error
+        if !interactive() || !Format::get().is_text() {
+            return None;
+        }
+        let fixed = unknown.fixed(suggestion);
+        let answer = prompt(&format!(
+            "Run `{} {}` instead? (y/N)",
+            env!("CARGO_PKG_NAME"),
+            fixed.join(" ")
+        ))
+        .ok()?;
+        if !answer.eq_ignore_ascii_case("y") {
+            return None;
+        }
+        self.parse_or_suggest(&fixed)
+    }
+
+    /// Finds the command named by the leading words, then reads flags with its spec.
+    pub fn parse(&self, args: &[String]) -> Result<Parsed, Box<dyn Error>> {
+        let args = self.expand(args);
+        let mut current = self;
+        let mut path = Vec::new();
+        let mut rest = Vec::new();
+
+        let mut iter = args.iter().enumerate();
+        while let Some((i, arg)) = iter.next() {
+            let cog = match current {
+                Self::Category(cog) => cog,
+                Self::Command(_) => {
+                    rest.push(arg.clone());
+                    continue;
+                }
+            };
+
+            if let Some(flag) = arg.strip_prefix("--") {
+                // command flags are not known yet, only global options take a value here
+                rest.push(arg.clone());
+                if global_takes_value(flag) {
+                    rest.extend(iter.next().map(|(_, value)| value.clone()));
+                }
+                continue;
+            }
+            if is_short(arg) {
+                rest.push(arg.clone());
+                continue;
+            }
+
+            match cog.get_key_value(arg.as_str()) {
+                Some((name, command)) => {
+                    path.push(*name);
+                    current = command;
+                }
+                None => {
+                    let mut names = cog
+                        .keys()
+                        .filter(|name| !is_hidden(name))
+                        .map(|name| name.to_string())
+                        .collect::<Vec<_>>();
+                    if path.is_empty() {
+                        names.extend(aliases().into_keys());
+                    }
+
+                    return Err(Unknown {
+                        message: format!("No such command `{arg}`"),
+                        suggestions: suggest(arg, names),
+                        path,
+                        args: args.clone(),
+                        index: i,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        let spec = match current {
+            Self::Command(spec) => *spec,
+            Self::Category(_) if path.is_empty() => {
+                return Err(CError::Usage("No command found".to_string()).into())
+            }
+            Self::Category(_) => {
+                return Err(
+                    CError::Usage(format!("`{}` needs a subcommand", path.join(" "))).into(),
+                )
+            }
+        };
+
+        let (flags, positional) = parse_flags(spec, rest, &path, &args)?;
+
+        Ok(Parsed {
+            path,
+            spec,
+            flags,
+            positional,
+        })
+    }
+
+    /// Replaces an alias at the start of the command line with what it stands for.
+    ///
+    /// Command names win over aliases, unless a namespace is used without a subcommand,
+    /// so `jobs` still runs `jobs list`.
+    pub fn expand(&self, args: &[String]) -> Vec<String> {
+        let aliases = aliases();
+        let mut args = args.to_vec();
+
+        // aliases may point at other aliases, the limit stops ones pointing at each other
+        for _ in 0..8 {
+            match self.expand_once(&aliases, &args) {
+                Some(expanded) => args = expanded,
+                None => break,
             }
-            Self::Category(cog) => cog
-                .get(args[0].as_str())
-                .unwrap()
-                .run(map, args.into_iter().skip(1).collect()),
         }
+        args
+    }
+
+    fn expand_once(
+        &self,
+        aliases: &HashMap<String, String>,
+        args: &[String],
+    ) -> Option<Vec<String>> {
+        let cog = match self {
+            Self::Category(cog) => cog,
+            Self::Command(_) => return None,
+        };
+        let words = words(args);
+        let i = *words.first()?;
+
+        let target = match (aliases.get(&args[i])?, cog.get(args[i].as_str())) {
+            (target, None) => target,
+            (target, Some(Self::Category(sub)))
+                if !words
+                    .get(1)
+                    .is_some_and(|next| sub.contains_key(args[*next].as_str())) =>
+            {
+                target
+            }
+            _ => return None,
+        };
+
+        debug!("Expanding alias `{}` to `{target}`", args[i]);
+        Some(
+            args[..i]
+                .iter()
+                .cloned()
+                .chain(target.split_whitespace().map(str::to_string))
+                .chain(args[i + 1..].iter().cloned())
+                .collect(),
+        )
+    }
+
+    /// Every command below this one along with its path, sorted by path.
+    /// Commands starting with `__` are meant for scripts and left out.
+    pub fn leaves(&self) -> Vec<(Vec<&'static str>, &'static Spec)> {
+        let mut leaves = match self {
+            Self::Command(spec) => vec![(Vec::new(), *spec)],
+            Self::Category(cog) => cog
+                .iter()
+                .filter(|(name, _)| !is_hidden(name))
+                .flat_map(|(name, command)| {
+                    command.leaves().into_iter().map(move |(mut path, spec)| {
+                        path.insert(0, *name);
+                        (path, spec)
+                    })
+                })
+                .collect(),
+        };
+        leaves.sort_by(|(this, _), (other, _)| this.cmp(other));
+        leaves
     }
 
     pub fn extend_map(self, map: &mut HashMap<&str, Self>) {
@@ -58,3 +281,197 @@ impl Command {
         }
     }
 }
+
+impl Parsed {
+    /// Runs the command and prints its result, errors are logged before being returned for the exit code.
+    pub fn run(self, map: HashMap<String, String>) -> Result<Output, Box<dyn Error>> {
+        let usage = format!("{} {}", self.path.join(" "), self.spec.usage());
+        let run = self.spec.run;
+
+        let args = match self.prepare(map) {
+            Ok(args) => args,
+            Err(e) => {
+                error!("{e}");
+                match e.downcast_ref::<CError>() {
+                    Some(e @ CError::NotLoggedIn) => info!("{}", e.remedy().unwrap()),
+                    _ => info!("Usage: {usage}"),
+                }
+                output::render_error(&*e, exit_code(&*e));
+                return Err(e);
+            }
+        };
+
+        let res = run(args);
+        match &res {
+            Ok(output) => {
+                debug!("Command finished with message `{}`", output.message);
+                output::render(output);
+            }
+            Err(e) => {
+                error!("Command exited with error `{e}`");
+                if let Some(remedy) = e.downcast_ref::<CError>().and_then(CError::remedy) {
+                    info!("{remedy}");
+                }
+                output::render_error(&**e, exit_code(&**e));
+            }
+        }
+        res
+    }
+
+    /// Checks the arguments against the spec, flags take priority over `map`.
+    pub fn prepare(self, mut map: HashMap<String, String>) -> Result<Args, Box<dyn Error>> {
+        map.extend(self.flags);
+        self.spec.prepare(&mut map, self.positional)?;
+        Ok(Args::new(self.spec, map))
+    }
+}
+
+/// Indexes of the words in a command line, leaving out flags and values of global options.
+fn words(args: &[String]) -> Vec<usize> {
+    let mut words = Vec::new();
+    let mut skip = false;
+    for (i, arg) in args.iter().enumerate() {
+        if std::mem::take(&mut skip) {
+            continue;
+        }
+        match arg.strip_prefix("--") {
+            Some(flag) => skip = global_takes_value(flag),
+            None if is_short(arg) => {}
+            None => words.push(i),
+        }
+    }
+    words
+}
+
+pub fn is_hidden(name: &str) -> bool {
+    name.starts_with("__")
+}
+
+/// Flags by name, and positional arguments in order.
+type SplitArgs = (HashMap<String, String>, Vec<String>);
+
+fn parse_flags(
+    spec: &Spec,
+    args: Vec<String>,
+    path: &[&'static str],
+    line: &[String],
+) -> Result<SplitArgs, Box<dyn Error>> {
+    let mut flags = HashMap::new();
+    let mut positional = Vec::new();
+
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        if is_short(&arg) {
+            for short in arg.chars().skip(1) {
+                match spec.find_short(short) {
+                    Some(found) => add_flag(&mut flags, found, String::new())?,
+                    None => {
+                        return Err(Unknown {
+                            message: format!("Unknown flag `-{short}`"),
+                            suggestions: Vec::new(),
+                            path: path.to_vec(),
+                            args: line.to_vec(),
+                            index: line
+                                .iter()
+                                .position(|word| *word == arg)
+                                .unwrap_or_default(),
+                        }
+                        .into())
+                    }
+                }
+            }
+            continue;
+        }
+
+        let flag = match arg.strip_prefix("--") {
+            Some(flag) => flag,
+            None => {
+                positional.push(arg);
+                continue;
+            }
+        };
+
+        let (key, inline) = match flag.split_once('=') {
+            Some((key, value)) => (key, Some(value.to_string())),
+            None => (flag, None),
+        };
+
+        let found = match spec.find(key) {
+            Some(found) => found,
+            None => {
+                let names = spec.args.iter().chain(GLOBAL).map(|arg| arg.name);
+                return Err(Unknown {
+                    message: format!("Unknown flag `--{key}`"),
+                    suggestions: suggest(key, names)
+                        .into_iter()
+                        .map(|name| format!("--{name}"))
+                        .collect(),
+                    path: path.to_vec(),
+                    args: line.to_vec(),
+                    index: line
+                        .iter()
+                        .position(|word| *word == arg)
+                        .unwrap_or_default(),
+                }
+                .into());
+            }
+        };
+
+        let value = match (found.kind.takes_value(), inline) {
+            (true, Some(value)) => value,
+            (false, Some(_)) => {
+                return Err(CError::Usage(format!("`--{key}` doesn't take a value")).into())
+            }
+            (false, None) => String::new(),
+            (true, None) => match iter.next() {
+                Some(value) if !value.starts_with("--") => value,
+                _ => return Err(CError::Usage(format!("`--{key}` needs a value")).into()),
+            },
+        };
+
+        add_flag(&mut flags, found, value)?;
+    }
+
+    Ok((flags, positional))
+}
+
+/// Counted flags add up when repeated, any other flag may only be given once.
+fn add_flag(
+    flags: &mut HashMap<String, String>,
+    arg: &Arg,
+    value: String,
+) -> Result<(), Box<dyn Error>> {
+    let name = arg.name.to_string();
+    if arg.kind == Kind::Count {
+        let count: usize = flags
+            .get(&name)
+            .map_or(0, |count| count.parse().unwrap_or(1));
+        flags.insert(name, (count + 1).to_string());
+        return Ok(());
+    }
+
+    if flags.insert(name, value).is_some() {
+        return Err(CError::Usage(format!("Duplicated flag `--{}`", arg.name)).into());
+    }
+    Ok(())
+}
+
+impl Unknown {
+    /// The command line with the unknown name replaced by `suggestion`.
+    pub fn fixed(&self, suggestion: &str) -> Vec<String> {
+        let mut args = self.args.clone();
+        args[self.index] = match args[self.index].split_once('=') {
+            Some((_, value)) if suggestion.starts_with("--") => format!("{suggestion}={value}"),
+            _ => suggestion.to_string(),
+        };
+        args
+    }
+}
+
+impl Display for Unknown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for Unknown {}
@@ -1,58 +1,43 @@
-use std::collections::HashMap;
 use std::error::Error;
 
-use goodmorning_bindings::services::v1::{V1Response, V1TokenOnly};
 use log::*;
 
+use crate::args::{Args, Spec};
 use crate::config::AccountConfig;
-use crate::error::Error as CError;
-use crate::functions::{map_args, post, type_yes};
+use crate::functions::type_yes;
+use crate::output::Output;
 use crate::traits::ConfigTriat;
 
-const ARGS: &[&str] = &[];
+pub const SPEC: Spec = Spec {
+    about: "Deletes the logged in account",
+    args: &[],
+    login: true,
+    examples: &["account delete"],
+    related: &["account logout"],
+    run: delete,
+};
 
-pub fn delete(
-    mut map: HashMap<String, String>,
-    args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
+pub fn delete(map: Args) -> Result<Output, Box<dyn Error>> {
+    warn!("Proceeding wipe your account from existence");
+    type_yes(&map)?;
+
+    map.client()?
+        .delete_account()
+        .inspect_err(|_| error!("Failed to delete account"))?;
+    info!("Your account has been successfully deleted");
 
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You are not logged in");
-        return Err(CError::StrErr("Not logged in").into());
+    let mut config = AccountConfig::load()?;
+    if let Some(name) = map.opt("account") {
+        config.remove(name);
     }
 
-    warn!("Proceeding wipe your account from existence");
-    type_yes(&map);
-
-    let instance = map.get("instance").unwrap();
-    let token = map.get("token").unwrap().to_string();
-    let url = format!("{}/api/accounts/v1/delete", instance);
-
-    let body = V1TokenOnly { token };
-
-    let res = post(&url, body, map.contains_key("http"))?;
-
-    match res {
-        V1Response::Error { kind } => {
-            error!("Failed to regenerate token");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::Deleted => {
-            info!("Your account has been successfully deleted");
-
-            if let Err(e) = AccountConfig::clear() {
-                error!(
-                    "However, saving to file failed, therefore you appear to still be logged on"
-                );
-                warn!("This appeared-to-be-logged-on is only client side, but the details are invalidated");
-                return Err(e);
-            } else {
-                warn!("Your account has been deleted from the server");
-            }
-        }
-        _ => unreachable!(),
+    if let Err(e) = config.save() {
+        error!("However, saving to file failed, therefore you appear to still be logged on");
+        warn!("This appeared-to-be-logged-on is only client side, but the details are invalidated");
+        return Err(e);
+    } else {
+        warn!("Your account has been deleted from the server");
     }
 
-    Ok(String::from("Saved"))
+    Ok(Output::new("Deleted").field("account", map.opt("account")))
 }
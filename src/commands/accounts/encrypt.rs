@@ -0,0 +1,85 @@
+use std::{env, error::Error};
+
+use log::*;
+
+use crate::args::{Args, Spec};
+use crate::config::{AccountConfig, Encryption};
+use crate::error::Error as CError;
+use crate::functions::{new_salt, prompt_password, unlock_with, yes, PASSPHRASE_ENV};
+use crate::output::Output;
+use crate::traits::ConfigTriat;
+
+pub const ENCRYPT_SPEC: Spec = Spec {
+    about: "Encrypt saved tokens with a passphrase",
+    args: &[],
+    login: false,
+    examples: &["account encrypt"],
+    related: &["account decrypt"],
+    run: encrypt,
+};
+
+pub fn encrypt(_map: Args) -> Result<Output, Box<dyn Error>> {
+    let mut config = AccountConfig::load()?;
+
+    if config.encryption.is_some() {
+        info!("Tokens are already encrypted");
+        return Ok(Output::new("Nothing changed"));
+    }
+
+    let passphrase = match env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let passphrase = prompt_password("New passphrase")?;
+            if prompt_password("Repeat passphrase")? != passphrase {
+                error!("Passphrases do not match");
+                return Err(CError::Usage("passphrase mismatch".to_string()).into());
+            }
+            passphrase
+        }
+    };
+
+    if passphrase.is_empty() {
+        error!("Passphrase cannot be empty");
+        return Err(CError::Usage("empty passphrase".to_string()).into());
+    }
+
+    let salt = new_salt();
+    unlock_with(&passphrase, &salt)?;
+    config.encryption = Some(Encryption { salt });
+    config.save()?;
+
+    info!("Tokens in {:?} are now encrypted", AccountConfig::path());
+    info!("You will be asked for the passphrase once per run, or set `{PASSPHRASE_ENV}`");
+
+    Ok(Output::new("Encrypted"))
+}
+
+pub const DECRYPT_SPEC: Spec = Spec {
+    about: "Store saved tokens in plain text again",
+    args: &[],
+    login: false,
+    examples: &["account decrypt"],
+    related: &["account encrypt"],
+    run: decrypt,
+};
+
+pub fn decrypt(map: Args) -> Result<Output, Box<dyn Error>> {
+    let mut config = AccountConfig::load()?;
+
+    if config.encryption.is_none() {
+        info!("Tokens are not encrypted");
+        return Ok(Output::new("Nothing changed"));
+    }
+
+    warn!(
+        "Your tokens will be stored in plain text in {:?}",
+        AccountConfig::path()
+    );
+    yes(&map)?;
+
+    config.encryption = None;
+    config.save()?;
+    info!("Tokens are now stored unencrypted");
+
+    Ok(Output::new("Decrypted"))
+}
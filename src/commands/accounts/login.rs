@@ -1,77 +1,90 @@
-use std::{collections::HashMap, error::Error};
+use std::error::Error;
 
-use goodmorning_bindings::services::v1::{V1PasswordId, V1Response};
 use log::*;
 
-use crate::config::AccountConfig;
+use crate::api::GmClient;
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::config::{Account, AccountConfig};
 use crate::error::Error as CError;
-use crate::functions::{map_args, post, prompt_not_present, prompt_password_not_present};
+use crate::functions::{prompt_not_present, prompt_password_not_present};
+use crate::output::Output;
 use crate::traits::ConfigTriat;
 
-const ARGS: &[&str] = &["username", "password"];
+pub const SPEC: Spec = Spec {
+    about: "Login to an account",
+    args: &[
+        Arg::pos("username")
+            .optional()
+            .help("Username string, such as `username:instance.com`"),
+        Arg::pos("password").optional().kind(Kind::Secret),
+        Arg::opt("name").help("Name to save the account as"),
+    ],
+    login: false,
+    examples: &[
+        "account login alice:gm.example.com",
+        "account login alice:localhost:8080 --name dev --http",
+    ],
+    related: &["account logout", "account switch", "account ls"],
+    run: login,
+};
 
-pub fn login(
-    mut map: HashMap<String, String>,
-    args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-
-    warn!(
-        "Your account ID and token will be stored in {:?}",
-        AccountConfig::path()
-    );
-    warn!("This means that anyone with permission to see that file will have access to your token, thus your account");
-    warn!("If you do not wish that to happen, exit with Ctrl + C");
+pub fn login(mut map: Args) -> Result<Output, Box<dyn Error>> {
+    if AccountConfig::read()?.encryption.is_none() {
+        warn!(
+            "Your account ID and token will be stored in {:?}",
+            AccountConfig::path()
+        );
+        warn!("This means that anyone with permission to see that file will have access to your token, thus your account");
+        warn!("If you do not wish that to happen, exit with Ctrl + C");
+        info!("Run `accounts encrypt` to protect saved tokens with a passphrase");
+    }
 
-    info!("If you have any other accounts on this device, continuing will overwrite that account's login");
+    info!("Other saved accounts are kept, use `switch` to change between them");
 
     prompt_not_present(
         "Username (e.g. username:instance.com)",
         "username",
         &mut map,
-    );
-    prompt_password_not_present("Password", "password", &mut map);
+    )?;
+    prompt_password_not_present("Password", "password", &mut map)?;
 
-    let user = map.get("username").unwrap().to_string();
+    let user = map.str("username").to_string();
     if !user.contains(':') {
         error!("This does not seem to be a valid username string");
-        return Err(CError::StringErr(format!("Invalid username string `{user}`")).into());
+        return Err(CError::Usage(format!("Invalid username string `{user}`")).into());
     }
     let (username, instance) = user.split_once(':').unwrap();
-    let password = map.get("password").unwrap().to_string();
 
-    let url = format!("{}/api/accounts/v1/login", instance);
+    let (id, token) = GmClient::new(instance, map.flag("http"))
+        .login(username, map.str("password"))
+        .inspect_err(|_| error!("Failed to log in"))?;
 
-    let body = V1PasswordId {
-        identifier: username.to_string(),
-        identifier_type: goodmorning_bindings::services::v1::V1IdentifierType::Username,
-        password,
+    let account = Account {
+        id,
+        instance: instance.to_string(),
+        token,
+        username: username.to_string(),
+        sealed: None,
     };
+    let name = map.opt("name").unwrap_or(&user).to_string();
 
-    let res: V1Response = post(&url, body, map.contains_key("http"))?;
-
-    match res {
-        V1Response::Error { kind } => {
-            error!("Failed to create account");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::Login { token, id } => {
-            let account = AccountConfig {
-                id,
-                instance: instance.to_string(),
-                token,
-            };
+    info!("Login successful");
 
-            info!("Login successful");
+    let mut config = AccountConfig::load()?;
+    if config.insert(name.clone(), account).is_some() {
+        info!("Replacing the saved login for `{name}`");
+    }
 
-            if let Err(e) = account.save() {
-                error!("However, saving to file failed, you will not stay logged in");
-                return Err(e);
-            } else {
-                info!("Token and ID are saved, and you will stay logged in")
-            }
-        }
-        _ => unreachable!(),
+    if let Err(e) = config.save() {
+        error!("However, saving to file failed, you will not stay logged in");
+        return Err(e);
+    } else {
+        info!("Token and ID are saved as `{name}`, and you will stay logged in")
     }
-    Ok("Ran".to_string())
+
+    Ok(Output::new("Ran")
+        .field("account", name)
+        .field("id", id)
+        .field("username", username)
+        .field("instance", instance))
 }
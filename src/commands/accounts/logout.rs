@@ -1,30 +1,47 @@
-use std::{collections::HashMap, error::Error};
+use std::error::Error;
 
 use log::*;
 
-use crate::error::Error as CError;
+use crate::args::{Args, Spec};
 use crate::functions::yes;
+use crate::output::Output;
 use crate::{config::AccountConfig, traits::ConfigTriat};
 
-pub fn logout(map: HashMap<String, String>, _args: Vec<String>) -> Result<String, Box<dyn Error>> {
-    if !AccountConfig::is_loggedin_map(&map) {
-        info!("You don't seemed to be logged in");
-        return Ok(String::from("Nothing changed"));
-    }
+pub const SPEC: Spec = Spec {
+    about: "Remove account from this device",
+    args: &[],
+    login: false,
+    examples: &["account logout", "account logout --account work"],
+    related: &["account login", "account delete"],
+    run: logout,
+};
+
+pub fn logout(map: Args) -> Result<Output, Box<dyn Error>> {
+    let name = match map.opt("account") {
+        Some(name) if map.logged_in() => name,
+        _ => {
+            info!("You don't seemed to be logged in");
+            return Ok(Output::new("Nothing changed"));
+        }
+    };
 
-    warn!("You are going to be logged out");
+    warn!("You are going to be logged out of `{name}`");
 
-    yes(&map);
+    yes(&map)?;
 
     let path = AccountConfig::path();
 
-    match AccountConfig::clear() {
-        Ok(_) => info!("Any account login info has been deleted"),
-        Err(e) => {
-            error!("Could not delete file at `{path:?}`");
-            return Err(CError::StringErr(e.to_string()).into());
-        }
+    let mut config = AccountConfig::load()?;
+    config.remove(name);
+
+    config
+        .save()
+        .inspect_err(|_| error!("Could not write file at `{path:?}`"))?;
+    info!("Login info for `{name}` has been deleted");
+
+    if !config.active.is_empty() {
+        info!("Switched to `{}`", config.active);
     }
 
-    Ok(String::from("Deleted"))
+    Ok(Output::new("Deleted").field("account", name))
 }
@@ -0,0 +1,62 @@
+use std::error::Error;
+
+use serde_json::json;
+
+use crate::args::{Args, Spec};
+use crate::config::AccountConfig;
+use crate::output::Output;
+use crate::traits::ConfigTriat;
+
+pub const SPEC: Spec = Spec {
+    about: "List saved accounts",
+    args: &[],
+    login: false,
+    examples: &["account ls"],
+    related: &["account switch", "account login"],
+    run: ls,
+};
+
+pub fn ls(_map: Args) -> Result<Output, Box<dyn Error>> {
+    let config = AccountConfig::load()?;
+
+    let accounts = config
+        .accounts
+        .iter()
+        .map(|(name, account)| {
+            json!({
+                "name": name,
+                "username": account.username,
+                "instance": account.instance,
+                "id": account.id,
+                "active": name == &config.active,
+            })
+        })
+        .collect::<Vec<_>>();
+    let output = Output::new("Listed").field("accounts", accounts);
+
+    if config.accounts.is_empty() {
+        return Ok(output.text("No saved accounts"));
+    }
+
+    let longest_name = config.accounts.keys().map(String::len).max().unwrap();
+
+    let lines = config
+        .accounts
+        .iter()
+        .map(|(name, account)| {
+            let user = if account.username.is_empty() {
+                account.instance.clone()
+            } else {
+                format!("{}:{}", account.username, account.instance)
+            };
+
+            format!(
+                "{} {name: <longest_name$} {user} (id {})",
+                if name == &config.active { "*" } else { " " },
+                account.id
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Ok(output.text(lines.join("\n")))
+}
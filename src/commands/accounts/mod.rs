@@ -1,23 +1,32 @@
 use std::collections::HashMap;
 
-use crate::traits::types::CommandFnType;
+use crate::args::Spec;
 
 mod create;
 mod delete;
+mod encrypt;
 mod login;
 mod logout;
+mod ls;
 mod regen;
 mod rename;
 mod status;
+mod switch;
+mod whoami;
 
-pub fn commands() -> HashMap<&'static str, CommandFnType> {
+pub fn commands() -> HashMap<&'static str, &'static Spec> {
     HashMap::from([
-        ("create", Box::new(create::create) as CommandFnType),
-        ("login", Box::new(login::login) as CommandFnType),
-        ("logout", Box::new(logout::logout) as CommandFnType),
-        ("regen", Box::new(regen::regen) as CommandFnType),
-        ("rename", Box::new(rename::rename) as CommandFnType),
-        ("delete", Box::new(delete::delete) as CommandFnType),
-        ("status", Box::new(status::status) as CommandFnType),
+        ("create", &create::SPEC),
+        ("login", &login::SPEC),
+        ("logout", &logout::SPEC),
+        ("regen", &regen::SPEC),
+        ("rename", &rename::SPEC),
+        ("delete", &delete::SPEC),
+        ("status", &status::SPEC),
+        ("switch", &switch::SPEC),
+        ("whoami", &whoami::SPEC),
+        ("ls", &ls::SPEC),
+        ("encrypt", &encrypt::ENCRYPT_SPEC),
+        ("decrypt", &encrypt::DECRYPT_SPEC),
     ])
 }
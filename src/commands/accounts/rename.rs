@@ -1,54 +1,31 @@
-use std::collections::HashMap;
 use std::error::Error;
 
-use goodmorning_bindings::services::v1::{V1RenameAccount, V1Response};
 use log::*;
 
-use crate::error::Error as CError;
-use crate::functions::{map_args, post, prompt_not_present};
-use crate::{config::AccountConfig, functions::yes};
+use crate::args::{Arg, Args, Spec};
+use crate::functions::yes;
+use crate::output::Output;
 
-const ARGS: &[&str] = &["newname"];
-
-pub fn rename(
-    mut map: HashMap<String, String>,
-    args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You are not logged in");
-        return Err(CError::StrErr("Not logged in").into());
-    }
+pub const SPEC: Spec = Spec {
+    about: "Change your username",
+    args: &[Arg::pos("newname").prompt("Your new username")],
+    login: true,
+    examples: &["account rename bob"],
+    related: &["account status"],
+    run: rename,
+};
 
+pub fn rename(map: Args) -> Result<Output, Box<dyn Error>> {
     warn!("Your username will be changed");
-    yes(&map);
-
-    prompt_not_present("Your new username", "newname", &mut map);
-
-    let instance = map.get("instance").unwrap();
-    let url = format!("{}/api/accounts/v1/rename", instance);
-
-    let new = map.get("newname").unwrap();
-    let token = map.get("token").unwrap().to_string();
-
-    let body = V1RenameAccount {
-        token,
-        new: new.to_string(),
-    };
+    yes(&map)?;
 
-    let res = post(&url, body, map.contains_key("http"))?;
+    let new = map.str("newname");
+    map.client()?
+        .rename(new)
+        .inspect_err(|_| error!("Failed to change name"))?;
 
-    match res {
-        V1Response::Error { kind } => {
-            error!("Failed to change name");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::Renamed => {
-            info!("Rename successful");
-            info!("Your new username is {}", new.to_lowercase());
-        }
-        _ => unreachable!(),
-    }
+    info!("Rename successful");
+    info!("Your new username is {}", new.to_lowercase());
 
-    Ok(String::from("Renamed"))
+    Ok(Output::new("Renamed").field("username", new.to_lowercase()))
 }
@@ -0,0 +1,35 @@
+use std::error::Error;
+
+use log::*;
+
+use crate::args::{Arg, Args, Spec};
+use crate::config::AccountConfig;
+use crate::error::Error as CError;
+use crate::output::Output;
+use crate::traits::ConfigTriat;
+
+pub const SPEC: Spec = Spec {
+    about: "Change the active account",
+    args: &[Arg::pos("name").prompt("Account name")],
+    login: false,
+    examples: &["account switch work"],
+    related: &["account ls", "account whoami"],
+    run: switch,
+};
+
+pub fn switch(map: Args) -> Result<Output, Box<dyn Error>> {
+    let name = map.str("name");
+    let mut config = AccountConfig::load()?;
+
+    if !config.accounts.contains_key(name) {
+        error!("No saved account named `{name}`");
+        info!("Run `accounts ls` to see all saved accounts");
+        return Err(CError::Usage(format!("no account named `{name}`")).into());
+    }
+
+    config.active = name.to_string();
+    config.save()?;
+    info!("Switched to `{name}`");
+
+    Ok(Output::new("Switched").field("account", name))
+}
@@ -0,0 +1,113 @@
+use std::{collections::BTreeMap, error::Error};
+
+use goodmorning_bindings::services::v1::V1Error;
+use log::*;
+
+use crate::args::{Arg, Args, Spec};
+use crate::config::{ENV_OVERRIDES, SOURCES};
+use crate::error::Error as CError;
+use crate::output::{print_text, Output};
+
+const KEYS: &[&str] = &["account", "id", "instance", "token", "http"];
+
+pub const SPEC: Spec = Spec {
+    about: "Check the stored session against the server",
+    args: &[Arg::flag("source").help("Show where each value came from")],
+    login: false,
+    examples: &["account whoami", "account whoami --source"],
+    related: &["account switch", "config list"],
+    run: whoami,
+};
+
+pub fn whoami(map: Args) -> Result<Output, Box<dyn Error>> {
+    let show_source = map.flag("source");
+    let sources = SOURCES.get();
+
+    let mut output = Output::new("Valid");
+    let mut found = BTreeMap::new();
+    let mut print_row = |key: &str, value: Option<String>, source: Option<String>| {
+        output = std::mem::take(&mut output).field(key, &value);
+        if let Some(source) = &source {
+            found.insert(key.to_string(), source.clone());
+        }
+
+        let value = value.unwrap_or_else(|| "\x1B[38;5;8m[not set]\x1B[0m".to_string());
+        if !show_source {
+            print_text(format!("{key: <8} {value}"));
+            return;
+        }
+
+        let source = source.unwrap_or_else(|| "\x1B[38;5;8m-\x1B[0m".to_string());
+        print_text(format!("{key: <8} {value: <24} {source}"));
+    };
+
+    for key in KEYS {
+        let value = match (map.opt(key), *key) {
+            (None, _) => None,
+            (Some(_), "http") => Some("true".to_string()),
+            (Some(token), "token") => Some(mask(token)),
+            (Some(value), _) => Some(value.to_string()),
+        };
+
+        print_row(key, value, sources.get(*key).map(ToString::to_string));
+    }
+
+    if show_source {
+        print_text(format!(
+            "\nPriority: command line flags > {} > config files\n",
+            ENV_OVERRIDES
+                .iter()
+                .map(|(var, _)| *var)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    if !map.logged_in() {
+        error!("You are not logged in");
+        return Err(CError::NotLoggedIn.into());
+    }
+
+    // reports the session as it is, instead of logging in again
+    let client = map.account_client()?;
+    match client.diritems("/") {
+        Ok(_) => print_row("session", Some("valid".to_string()), None),
+        Err(CError::Server {
+            endpoint,
+            kind: V1Error::InvalidToken,
+        }) => {
+            print_row("session", Some("invalid".to_string()), None);
+            error!("The server rejected the stored token");
+            return Err(CError::Server {
+                endpoint,
+                kind: V1Error::InvalidToken,
+            }
+            .into());
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    let (_, account) = client
+        .profile_by_id(client.id)
+        .inspect_err(|_| error!("Cannot load profile"))?;
+    let server = Some("server".to_string());
+    print_row("username", Some(account.username), server.clone());
+    print_row("status", Some(account.status), server.clone());
+    print_row(
+        "verified",
+        Some(if account.verified { "yes" } else { "no" }.to_string()),
+        server,
+    );
+
+    if show_source {
+        output = output.field("sources", found);
+    }
+    Ok(output)
+}
+
+fn mask(token: &str) -> String {
+    if token.len() <= 8 {
+        return "*".repeat(token.len());
+    }
+    format!("{}...", &token[..4])
+}
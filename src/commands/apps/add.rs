@@ -0,0 +1,67 @@
+use std::error::Error;
+
+use log::*;
+
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::config::{ApplicationsConfig, Rule};
+use crate::error::Error as CError;
+use crate::output::Output;
+use crate::traits::ConfigTriat;
+
+pub const SPEC: Spec = Spec {
+    about: "Add a rule for opening files",
+    args: &[
+        Arg::pos("command").prompt("Command (use `{path}` as a placeholder)"),
+        Arg::opt("glob").help("Match the file name against a pattern"),
+        Arg::opt("ext").help("Match the extension, such as `pdf`"),
+        Arg::opt("mime").help("Match the MIME type, such as `image/*`"),
+        Arg::opt("at")
+            .kind(Kind::Uint)
+            .help("Position of the rule, defaults to last"),
+        Arg::flag("detach").help("Run the command in the background"),
+    ],
+    login: false,
+    examples: &[
+        "apps add 'zathura {path}' --ext pdf --detach",
+        "apps add 'feh {path}' --mime 'image/*' --at 1",
+    ],
+    related: &["apps ls", "apps test"],
+    run: add,
+};
+
+pub fn add(map: Args) -> Result<Output, Box<dyn Error>> {
+    let rule = Rule {
+        glob: map.opt("glob").map(str::to_string),
+        ext: map.opt("ext").map(str::to_string),
+        mime: map.opt("mime").map(str::to_string),
+        command: map.str("command").to_string(),
+        detach: map.flag("detach"),
+    };
+
+    if !rule.command.contains("{path}") {
+        warn!("The command has no `{{path}}` placeholder, the file will not be passed to it");
+    }
+    if rule.glob.is_none() && rule.ext.is_none() && rule.mime.is_none() {
+        warn!("No `--glob`, `--ext` or `--mime` given, this rule matches every file");
+    }
+
+    let mut config = ApplicationsConfig::read()?;
+    let position = match map.opt("at") {
+        Some(_) => match map.uint("at")? as usize {
+            at if (1..=config.rules.len() + 1).contains(&at) => at - 1,
+            _ => {
+                error!("`--at` must be between 1 and {}", config.rules.len() + 1);
+                return Err(CError::Usage("invalid position".to_string()).into());
+            }
+        },
+        None => config.rules.len(),
+    };
+
+    info!("Added rule {}: {}", position + 1, rule.conditions());
+    config.rules.insert(position, rule.clone());
+    config.save()?;
+
+    Ok(Output::new("Added")
+        .field("index", position + 1)
+        .field("rule", rule))
+}
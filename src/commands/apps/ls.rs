@@ -0,0 +1,59 @@
+use std::error::Error;
+
+use crate::args::{Args, Spec};
+use crate::config::ApplicationsConfig;
+use crate::output::Output;
+use crate::traits::ConfigTriat;
+
+pub const SPEC: Spec = Spec {
+    about: "List rules for opening files",
+    args: &[],
+    login: false,
+    examples: &["apps ls"],
+    related: &["apps add", "apps remove"],
+    run: ls,
+};
+
+pub fn ls(_map: Args) -> Result<Output, Box<dyn Error>> {
+    let config = ApplicationsConfig::read()?;
+
+    let mut lines = Vec::new();
+    if config.rules.is_empty() {
+        lines.push("No rules".to_string());
+    }
+
+    let conditions = config
+        .rules
+        .iter()
+        .map(|rule| rule.conditions())
+        .collect::<Vec<_>>();
+    let longest = conditions.iter().map(String::len).max().unwrap_or_default();
+
+    config
+        .rules
+        .iter()
+        .zip(conditions)
+        .enumerate()
+        .for_each(|(i, (rule, conditions))| {
+            lines.push(format!(
+                "{: >3}. {conditions: <longest$}  {}{}",
+                i + 1,
+                rule.command,
+                if rule.detach { " (background)" } else { "" }
+            ))
+        });
+
+    lines.push(format!(
+        "\nFallback: {}",
+        if config.fallback {
+            "$EDITOR for text, xdg-open for anything else"
+        } else {
+            "ask for a command"
+        }
+    ));
+
+    Ok(Output::new("Listed")
+        .text(lines.join("\n"))
+        .field("rules", config.rules)
+        .field("fallback", config.fallback))
+}
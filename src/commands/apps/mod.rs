@@ -0,0 +1,17 @@
+use std::collections::HashMap;
+
+use crate::args::Spec;
+
+mod add;
+mod ls;
+mod remove;
+mod test;
+
+pub fn commands() -> HashMap<&'static str, &'static Spec> {
+    HashMap::from([
+        ("ls", &ls::SPEC),
+        ("add", &add::SPEC),
+        ("remove", &remove::SPEC),
+        ("test", &test::SPEC),
+    ])
+}
@@ -0,0 +1,38 @@
+use std::error::Error;
+
+use log::*;
+
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::config::ApplicationsConfig;
+use crate::error::Error as CError;
+use crate::output::Output;
+use crate::traits::ConfigTriat;
+
+pub const SPEC: Spec = Spec {
+    about: "Remove a rule",
+    args: &[Arg::pos("index").kind(Kind::Uint).prompt("Rule number")],
+    login: false,
+    examples: &["apps remove 2"],
+    related: &["apps ls"],
+    run: remove,
+};
+
+pub fn remove(map: Args) -> Result<Output, Box<dyn Error>> {
+    let mut config = ApplicationsConfig::read()?;
+    let index = match map.uint("index")? as usize {
+        index if (1..=config.rules.len()).contains(&index) => index - 1,
+        _ => {
+            error!("No rule with that number");
+            info!("Run `apps ls` to see all rules");
+            return Err(CError::Usage("invalid rule number".to_string()).into());
+        }
+    };
+
+    let rule = config.rules.remove(index);
+    config.save()?;
+    info!("Removed rule {}: {}", index + 1, rule.conditions());
+
+    Ok(Output::new("Removed")
+        .field("index", index + 1)
+        .field("rule", rule))
+}
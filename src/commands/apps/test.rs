@@ -0,0 +1,53 @@
+use std::{error::Error, path::PathBuf};
+
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::config::ApplicationsConfig;
+use crate::output::Output;
+use crate::traits::ConfigTriat;
+
+pub const SPEC: Spec = Spec {
+    about: "Show which command opens a file",
+    args: &[Arg::pos("path").kind(Kind::File).prompt("File path")],
+    login: false,
+    examples: &["apps test report.pdf"],
+    related: &["apps ls", "storage cat"],
+    run: test,
+};
+
+pub fn test(map: Args) -> Result<Output, Box<dyn Error>> {
+    let path = PathBuf::from(map.str("path"));
+    let config = ApplicationsConfig::read()?;
+
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+    let mut lines = vec![format!("MIME type: {mime}")];
+    let mut output = Output::new("Tested").field("mime", mime.to_string());
+
+    match config.command(&path) {
+        (command, Some(i)) => {
+            lines.push(format!(
+                "Matched rule {}: {}",
+                i + 1,
+                config.rules[i].conditions()
+            ));
+            lines.push(format!("Command: {command}"));
+            if config.rules[i].detach {
+                lines.push("Runs in the background".to_string());
+            }
+            output = output
+                .field("rule", i + 1)
+                .field("command", command)
+                .field("detach", config.rules[i].detach);
+        }
+        (_, None) if !config.fallback => {
+            lines.push("No rule matched, you will be asked for a command".to_string());
+            output = output.field("rule", ()).field("command", ());
+        }
+        (command, None) => {
+            lines.push("No rule matched, using fallback".to_string());
+            lines.push(format!("Command: {command}"));
+            output = output.field("rule", ()).field("command", command);
+        }
+    }
+
+    Ok(output.text(lines.join("\n")))
+}
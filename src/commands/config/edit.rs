@@ -0,0 +1,123 @@
+use std::{
+    env,
+    error::Error,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use log::*;
+use serde_yaml::Value;
+
+use super::file;
+use crate::args::{Arg, Args, Spec};
+use crate::error::Error as CError;
+use crate::functions::{interactive, prompt};
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Edit a config file with $EDITOR",
+    args: &[Arg::pos("name").prompt("Config name (e.g. account)")],
+    login: false,
+    examples: &["config edit defaults"],
+    related: &["config path"],
+    run: edit,
+};
+
+pub fn edit(map: Args) -> Result<Output, Box<dyn Error>> {
+    let file = file(map.str("name"))?;
+    // the file as it is, so one that no longer parses can still be fixed here
+    let original = match fs::read_to_string((file.path)()) {
+        Ok(original) => original,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => serde_yaml::to_string(&(file.read)()?)?,
+        Err(e) => return Err(CError::io(&(file.path)(), e).into()),
+    };
+
+    let draft = Draft::create(
+        &dirs::cache_dir()
+            .unwrap()
+            .join(env!("CARGO_PKG_NAME"))
+            .join(format!("{}.edit.yml", file.name)),
+        &original,
+    )?;
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    let value = loop {
+        info!("Opening {:?} with `{editor}`", draft.0);
+        // the editor may come with arguments, such as `code -w`
+        let status = execute::command(&editor).arg(&draft.0).status()?;
+        if !status.success() {
+            error!(
+                "Editor exited with code {}, nothing saved",
+                status.code().unwrap_or(-1)
+            );
+            return Err(CError::Other("editor failed".to_string()).into());
+        }
+
+        let edited = fs::read_to_string(&draft.0)?;
+        if edited == original {
+            info!("No changes made");
+            return Ok(Output::new("Nothing changed"));
+        }
+
+        let value = serde_yaml::from_str::<Value>(&edited)
+            .map_err(|e| -> Box<dyn Error> {
+                CError::config((file.path)(), format!("invalid YAML: {e}")).into()
+            })
+            .and_then(|value| (file.validate)(&value).map(|_| value));
+
+        match value {
+            Ok(value) => break value,
+            Err(e) => {
+                error!("{e}");
+                if !interactive() || !prompt("Edit again? (y/n)")?.trim().starts_with('y') {
+                    error!("Config not saved, your edits are discarded");
+                    return Err(CError::Other("invalid config".to_string()).into());
+                }
+            }
+        }
+    };
+
+    (file.write)(&value)?;
+    info!("Saved {:?}", (file.path)());
+
+    Ok(Output::new("Saved"))
+}
+
+/// The copy being edited, removed however the edit ends since it may hold plain tokens.
+struct Draft(PathBuf);
+
+impl Draft {
+    /// Creates the draft readable by the owner only, replacing one left by an earlier edit.
+    fn create(path: &Path, contents: &str) -> io::Result<Self> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(path)?;
+        let draft = Self(path.to_path_buf());
+        file.write_all(contents.as_bytes())?;
+        Ok(draft)
+    }
+}
+
+impl Drop for Draft {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.0) {
+            warn!("Failed to remove {:?}: {e}", self.0);
+        }
+    }
+}
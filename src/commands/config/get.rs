@@ -0,0 +1,47 @@
+use std::error::Error;
+
+use log::*;
+
+use super::{flatten, lookup, split_key};
+use crate::args::{Arg, Args, Spec};
+use crate::error::Error as CError;
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Print a config value, such as `account.active`",
+    args: &[Arg::pos("key").prompt("Key (e.g. account.active)")],
+    login: false,
+    examples: &[
+        "config get account.active",
+        "config get applications.fallback",
+    ],
+    related: &["config set", "config list"],
+    run: get,
+};
+
+pub fn get(map: Args) -> Result<Output, Box<dyn Error>> {
+    let key = map.str("key");
+    let (file, path) = split_key(key)?;
+    let value = (file.read)()?;
+
+    let value = match lookup(&value, &path) {
+        Some(value) => value,
+        None => {
+            error!("Key `{key}` is not set");
+            return Err(CError::Usage(format!("no such key `{key}`")).into());
+        }
+    };
+
+    let mut lines = Vec::new();
+    flatten(key, value, &mut lines);
+    let text = if lines.len() == 1 && !value.is_mapping() && !value.is_sequence() {
+        lines[0].split_once(" = ").unwrap().1.to_string()
+    } else {
+        lines.join("\n")
+    };
+
+    Ok(Output::new("Shown")
+        .text(text)
+        .field("key", key)
+        .field("value", value))
+}
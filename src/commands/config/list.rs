@@ -0,0 +1,31 @@
+use std::error::Error;
+
+use super::{file, files, flatten};
+use crate::args::{Arg, Args, Spec};
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "List all config values",
+    args: &[Arg::pos("name").optional()],
+    login: false,
+    examples: &["config list", "config list instances"],
+    related: &["config get"],
+    run: list,
+};
+
+pub fn list(map: Args) -> Result<Output, Box<dyn Error>> {
+    let files = match map.opt("name") {
+        Some(name) => vec![file(name)?],
+        None => files(),
+    };
+
+    let mut lines = Vec::new();
+    let mut output = Output::new("Listed");
+    for file in files {
+        let value = (file.read)()?;
+        flatten(file.name, &value, &mut lines);
+        output = output.field(file.name, value);
+    }
+
+    Ok(output.text(lines.join("\n")))
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::PathBuf;
+
+use log::*;
+use serde_yaml::{Mapping, Value};
+
+use crate::args::Spec;
+use crate::config::{
+    AccountConfig, AliasesConfig, ApplicationsConfig, DefaultsConfig, InstancesConfig,
+};
+use crate::error::Error as CError;
+use crate::traits::ConfigTriat;
+
+mod edit;
+mod get;
+mod list;
+mod path;
+mod set;
+
+pub fn commands() -> HashMap<&'static str, &'static Spec> {
+    HashMap::from([
+        ("get", &get::SPEC),
+        ("set", &set::SET_SPEC),
+        ("unset", &set::UNSET_SPEC),
+        ("list", &list::SPEC),
+        ("edit", &edit::SPEC),
+        ("path", &path::SPEC),
+    ])
+}
+
+/// A config file that can be edited as raw YAML.
+struct ConfigFile {
+    name: &'static str,
+    path: fn() -> PathBuf,
+    read: fn() -> Result<Value, Box<dyn Error>>,
+    write: fn(&Value) -> Result<(), Box<dyn Error>>,
+    validate: fn(&Value) -> Result<(), Box<dyn Error>>,
+}
+
+fn entry<C: ConfigTriat>() -> ConfigFile {
+    ConfigFile {
+        name: C::NAME,
+        path: C::path,
+        read: C::read_value,
+        write: C::write_value,
+        validate: C::validate,
+    }
+}
+
+fn files() -> Vec<ConfigFile> {
+    vec![
+        entry::<AccountConfig>(),
+        entry::<AliasesConfig>(),
+        entry::<ApplicationsConfig>(),
+        entry::<DefaultsConfig>(),
+        entry::<InstancesConfig>(),
+    ]
+}
+
+fn file(name: &str) -> Result<ConfigFile, Box<dyn Error>> {
+    match files().into_iter().find(|file| file.name == name) {
+        Some(file) => Ok(file),
+        None => {
+            error!("No config named `{name}`");
+            info!(
+                "Available configs are: {}",
+                files()
+                    .iter()
+                    .map(|file| format!("`{}`", file.name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            Err(CError::Usage(format!("unknown config `{name}`")).into())
+        }
+    }
+}
+
+/// Splits `account.accounts.lab.token` into the config file and the key path inside it.
+fn split_key(key: &str) -> Result<(ConfigFile, Vec<&str>), Box<dyn Error>> {
+    let mut segments = key.split('.').filter(|segment| !segment.is_empty());
+    let file = file(segments.next().unwrap_or_default())?;
+    Ok((file, segments.collect()))
+}
+
+fn lookup<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, segment| match value {
+        Value::Mapping(map) => map.get(*segment),
+        Value::Sequence(seq) => seq.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// Like `lookup`, but creates missing mappings along the way.
+fn lookup_mut<'a>(value: &'a mut Value, path: &[&str]) -> Option<&'a mut Value> {
+    path.iter().try_fold(value, |value, segment| {
+        if value.is_null() {
+            *value = Value::Mapping(Mapping::new());
+        }
+
+        match value {
+            Value::Mapping(map) => {
+                let key = Value::String(segment.to_string());
+                if !map.contains_key(&key) {
+                    map.insert(key.clone(), Value::Null);
+                }
+                map.get_mut(&key)
+            }
+            Value::Sequence(seq) => seq.get_mut(segment.parse::<usize>().ok()?),
+            _ => None,
+        }
+    })
+}
+
+/// Flattens a YAML value into `key.path = value` lines, hiding tokens.
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Mapping(map) => map.iter().for_each(|(key, value)| {
+            let key = match key {
+                Value::String(s) => s.clone(),
+                other => serde_yaml::to_string(other).unwrap().trim().to_string(),
+            };
+            flatten(&format!("{prefix}.{key}"), value, out)
+        }),
+        Value::Sequence(seq) => seq
+            .iter()
+            .enumerate()
+            .for_each(|(i, value)| flatten(&format!("{prefix}.{i}"), value, out)),
+        value => out.push(format!("{prefix} = {}", display(prefix, value))),
+    }
+}
+
+fn display(key: &str, value: &Value) -> String {
+    match value {
+        Value::String(s) if key == "token" || key.ends_with(".token") => {
+            if s.is_empty() {
+                String::new()
+            } else {
+                "[hidden]".to_string()
+            }
+        }
+        Value::String(s) => s.clone(),
+        Value::Null => "~".to_string(),
+        other => serde_yaml::to_string(other).unwrap().trim().to_string(),
+    }
+}
@@ -0,0 +1,26 @@
+use std::error::Error;
+
+use super::file;
+use crate::args::{Arg, Args, Spec};
+use crate::output::Output;
+use crate::traits::config_dir;
+
+pub const SPEC: Spec = Spec {
+    about: "Print where config files are stored",
+    args: &[Arg::pos("name").optional()],
+    login: false,
+    examples: &["config path", "config path defaults"],
+    related: &["config edit"],
+    run: path,
+};
+
+pub fn path(map: Args) -> Result<Output, Box<dyn Error>> {
+    let path = match map.opt("name") {
+        Some(name) => (file(name)?.path)(),
+        None => config_dir(),
+    };
+
+    Ok(Output::new("Shown")
+        .text(path.display().to_string())
+        .field("path", path))
+}
@@ -0,0 +1,121 @@
+use std::error::Error;
+
+use log::*;
+use serde_yaml::Value;
+
+use super::{lookup_mut, split_key};
+use crate::args::{Arg, Args, Spec};
+use crate::error::Error as CError;
+use crate::output::Output;
+
+pub const SET_SPEC: Spec = Spec {
+    about: "Change a config value after validating it",
+    args: &[
+        Arg::pos("key").prompt("Key (e.g. account.active)"),
+        Arg::pos("value").prompt("Value"),
+    ],
+    login: false,
+    examples: &[
+        "config set applications.fallback false",
+        "config set defaults.global.yes true",
+    ],
+    related: &["config unset", "config get"],
+    run: set,
+};
+
+pub fn set(map: Args) -> Result<Output, Box<dyn Error>> {
+    let key = map.str("key");
+    let raw = map.str("value");
+    let (file, path) = split_key(key)?;
+
+    if path.is_empty() {
+        error!(
+            "Cannot replace the whole config, use `config edit {}`",
+            file.name
+        );
+        return Err(CError::Usage("missing key".to_string()).into());
+    }
+
+    // values are read as YAML first so numbers and booleans keep their type,
+    // then as a plain string if the config expects one
+    let mut candidates = Vec::new();
+    if let Ok(parsed) = serde_yaml::from_str::<Value>(raw) {
+        candidates.push(parsed);
+    }
+    candidates.push(Value::String(raw.to_string()));
+
+    let original = (file.read)()?;
+    let mut last_error = None;
+    for candidate in candidates {
+        let mut value = original.clone();
+        match lookup_mut(&mut value, &path) {
+            Some(slot) => *slot = candidate.clone(),
+            None => {
+                error!("Cannot set `{key}`, a parent is not a mapping or list");
+                return Err(CError::Usage(format!("invalid key `{key}`")).into());
+            }
+        }
+
+        match (file.validate)(&value) {
+            Ok(()) => {
+                (file.write)(&value)?;
+                info!("Set `{key}`");
+                return Ok(Output::new("Set")
+                    .field("key", key)
+                    .field("value", candidate));
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    error!("Value not saved");
+    Err(last_error.unwrap())
+}
+
+pub const UNSET_SPEC: Spec = Spec {
+    about: "Remove a config value",
+    args: &[Arg::pos("key").prompt("Key (e.g. account.active)")],
+    login: false,
+    examples: &["config unset defaults.global.yes"],
+    related: &["config set"],
+    run: unset,
+};
+
+pub fn unset(map: Args) -> Result<Output, Box<dyn Error>> {
+    let key = map.str("key");
+    let (file, path) = split_key(key)?;
+
+    let (last, parents) = match path.split_last() {
+        Some(split) => split,
+        None => {
+            error!(
+                "Cannot remove the whole config, delete {:?} instead",
+                (file.path)()
+            );
+            return Err(CError::Usage("missing key".to_string()).into());
+        }
+    };
+
+    let mut value = (file.read)()?;
+    let removed = match super::lookup_mut(&mut value, parents) {
+        Some(Value::Mapping(map)) => map.remove(*last).is_some(),
+        Some(Value::Sequence(seq)) => match last.parse::<usize>() {
+            Ok(i) if i < seq.len() => {
+                seq.remove(i);
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    };
+
+    if !removed {
+        warn!("Key `{key}` is not set");
+        return Ok(Output::new("Nothing changed").field("key", key));
+    }
+
+    (file.write)(&value)?;
+    info!("Removed `{key}`");
+
+    Ok(Output::new("Unset").field("key", key))
+}
@@ -1,64 +1,58 @@
 use std::any::Any;
-use std::{collections::HashMap, error::Error};
+use std::error::Error;
 
-use goodmorning_bindings::services::v1::{V1Job, V1Response, V1TokenOnly};
+use goodmorning_bindings::services::v1::V1Job;
 use goodmorning_bindings::structs::TexCompileDisplay;
 use goodmorning_bindings::traits::SerdeAny;
 use log::*;
 
-use crate::config::AccountConfig;
-use crate::error::Error as CError;
-use crate::functions::post;
+use crate::args::{Args, Spec};
+use crate::output::Output;
 
-pub fn jobs(map: HashMap<String, String>, _args: Vec<String>) -> Result<String, Box<dyn Error>> {
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You are not logged in");
-        return Err(CError::StrErr("Not logged in").into());
-    }
-
-    let instance = map.get("instance").unwrap();
-    let url = format!("{}/api/jobs/v1/jobs", instance);
-    let token = map.get("token").unwrap().to_string();
-
-    let body = V1TokenOnly { token };
+pub const SPEC: Spec = Spec {
+    about: "View current jobs",
+    args: &[],
+    login: true,
+    examples: &["jobs list"],
+    related: &["jobs unqueue", "tex compile"],
+    run: jobs,
+};
 
-    let res = post(&url, body, map.contains_key("http"))?;
+pub fn jobs(map: Args) -> Result<Output, Box<dyn Error>> {
+    let (current, queue) = map
+        .client()?
+        .jobs()
+        .inspect_err(|_| error!("Failed to load jobs"))?;
 
-    match res {
-        V1Response::Error { kind } => {
-            error!("Failed to change name");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::Jobs { current, queue } => {
-            if current.is_empty() && queue.is_empty() {
-                println!("You have no jobs");
-                return Ok("No jobs".to_string());
-            }
-            if !queue.is_empty() {
-                println!(
-                    "Queued:\n{}",
-                    queue
-                        .iter()
-                        .map(|job| format!("\t{}", job_display(job)))
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                )
-            }
-            if !current.is_empty() {
-                println!(
-                    "Running jobs:\n{}",
-                    current
-                        .iter()
-                        .map(|job| format!("\t{}", job_display(job)))
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                )
-            }
-        }
-        _ => unreachable!(),
+    let output = Output::new("Listed")
+        .field("current", &current)
+        .field("queue", &queue);
+    if current.is_empty() && queue.is_empty() {
+        return Ok(output.text("You have no jobs"));
     }
 
-    Ok(String::from("Deleted"))
+    let mut sections = Vec::new();
+    if !queue.is_empty() {
+        sections.push(format!(
+            "Queued:\n{}",
+            queue
+                .iter()
+                .map(|job| format!("\t{}", job_display(job)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+    if !current.is_empty() {
+        sections.push(format!(
+            "Running jobs:\n{}",
+            current
+                .iter()
+                .map(|job| format!("\t{}", job_display(job)))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+    Ok(output.text(sections.join("\n")))
 }
 
 fn job_display(job: &V1Job) -> String {
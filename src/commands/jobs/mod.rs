@@ -2,14 +2,11 @@
 
 use std::collections::HashMap;
 
-use crate::traits::types::CommandFnType;
+use crate::args::Spec;
 
 mod jobs;
 mod unqueue;
 
-pub fn commands() -> HashMap<&'static str, CommandFnType> {
-    HashMap::from([
-        ("jobs", Box::new(jobs::jobs) as CommandFnType),
-        ("unqueue", Box::new(unqueue::unqueue) as CommandFnType),
-    ])
+pub fn commands() -> HashMap<&'static str, &'static Spec> {
+    HashMap::from([("list", &jobs::SPEC), ("unqueue", &unqueue::SPEC)])
 }
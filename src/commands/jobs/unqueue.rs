@@ -1,48 +1,26 @@
-use std::collections::HashMap;
 use std::error::Error;
 
-use goodmorning_bindings::services::v1::{V1Response, V1Unqueue};
 use log::*;
 
-use crate::error::Error as CError;
-use crate::functions::{map_args, post, prompt_not_present};
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::output::Output;
 
-use crate::config::AccountConfig;
+pub const SPEC: Spec = Spec {
+    about: "Remove a job from the queue",
+    args: &[Arg::pos("taskid").kind(Kind::Uint).prompt("Task ID")],
+    login: true,
+    examples: &["jobs unqueue 12"],
+    related: &["jobs list"],
+    run: unqueue,
+};
 
-const ARGS: &[&str] = &["taskid"];
+pub fn unqueue(map: Args) -> Result<Output, Box<dyn Error>> {
+    let taskid = map.uint("taskid")?;
 
-pub fn unqueue(
-    mut map: HashMap<String, String>,
-    args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You are not logged in");
-        return Err(CError::StrErr("Not logged in").into());
-    }
+    map.client()?
+        .unqueue(taskid)
+        .inspect_err(|_| error!("Failed to unqueue job"))?;
+    info!("Unqueued");
 
-    prompt_not_present("Task ID", "taskid", &mut map);
-
-    let instance = map.get("instance").unwrap();
-    let url = format!("{}/api/jobs/v1/unqueue", instance);
-
-    let taskid = map.get("taskid").unwrap().parse::<u64>()?;
-    let token = map.get("token").unwrap().to_string();
-
-    let body = V1Unqueue { token, id: taskid };
-
-    let res = post(&url, body, map.contains_key("http"))?;
-
-    match res {
-        V1Response::Error { kind } => {
-            error!("Failed to regenerate token");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::Unqueued => {
-            info!("Unqueued")
-        }
-        _ => unreachable!(),
-    }
-
-    Ok(String::from("Saved"))
+    Ok(Output::new("Unqueued").field("id", taskid))
 }
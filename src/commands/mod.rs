@@ -1,20 +1,93 @@
 use std::collections::HashMap;
 
+use crate::config::AliasesConfig;
 use crate::Command;
 
 mod accounts;
+mod apps;
+mod config;
 mod jobs;
 mod storage;
 pub mod tex;
 mod utils;
 
+/// Names from before commands were grouped into namespaces, they keep working as aliases.
+pub const BUILTIN_ALIASES: &[(&str, &str)] = &[
+    ("accounts", "account"),
+    ("create", "account create"),
+    ("delete", "account delete"),
+    ("login", "account login"),
+    ("logout", "account logout"),
+    ("regen", "account regen"),
+    ("rename", "account rename"),
+    ("status", "account status"),
+    ("switch", "account switch"),
+    ("whoami", "account whoami"),
+    ("cat", "storage cat"),
+    ("cp", "storage cp"),
+    ("fs", "storage fs"),
+    ("ls", "storage ls"),
+    ("mkdir", "storage mkdir"),
+    ("mv", "storage mv"),
+    ("pack", "storage pack"),
+    ("rm", "storage rm"),
+    ("touch", "storage touch"),
+    ("unpack", "storage unpack"),
+    ("upload", "storage upload"),
+    ("vis", "storage vis"),
+    ("compile", "tex compile"),
+    ("publish", "tex publish"),
+    ("publishes", "tex publishes"),
+    ("tpfedit", "tex pfedit"),
+    ("tpfpedit", "tex pfpedit"),
+    ("tprofile", "tex profile"),
+    ("jobs", "jobs list"),
+    ("unqueue", "jobs unqueue"),
+];
+
 pub fn commands() -> Command {
     let mut map: HashMap<&str, Command> = HashMap::new();
-    // map.insert("tex", tex::commands().into());
-    Command::from(tex::commands()).extend_map(&mut map);
-    Command::from(accounts::commands()).extend_map(&mut map);
-    Command::from(storage::commands()).extend_map(&mut map);
-    Command::from(utils::commands()).extend_map(&mut map);
-    Command::from(jobs::commands()).extend_map(&mut map);
+    sections()
+        .into_iter()
+        .for_each(|(_, section)| section.extend_map(&mut map));
     map.into()
 }
+
+/// Top level commands grouped the way `help` and the man page list them.
+pub fn sections() -> Vec<(&'static str, Command)> {
+    let mut utility: HashMap<&str, Command> = HashMap::new();
+    utility.insert("apps", apps::commands().into());
+    utility.insert("config", config::commands().into());
+    Command::from(utils::commands()).extend_map(&mut utility);
+
+    vec![
+        ("utility", utility.into()),
+        ("account", namespace("account", accounts::commands().into())),
+        ("storage", namespace("storage", storage::commands().into())),
+        ("tex", namespace("tex", tex::commands().into())),
+        ("jobs", namespace("jobs", jobs::commands().into())),
+    ]
+}
+
+fn namespace(name: &'static str, commands: Command) -> Command {
+    HashMap::from([(name, commands)]).into()
+}
+
+/// Built in aliases, overridden by the ones in `aliases.yml`.
+pub fn aliases() -> HashMap<String, String> {
+    let mut aliases = BUILTIN_ALIASES
+        .iter()
+        .map(|(alias, target)| (alias.to_string(), target.to_string()))
+        .collect::<HashMap<_, _>>();
+    aliases.extend(AliasesConfig::loaded());
+    aliases
+}
+
+/// A command line with its alias expanded, such as `publishes` to `tex publishes`.
+pub fn canonical(line: &str) -> String {
+    let words = line
+        .split_whitespace()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    commands().expand(&words).join(" ")
+}
@@ -0,0 +1,132 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::error::Error as CError;
+use crate::output::Output;
+
+/// Completing a path usually lists the same directory a few times in a row.
+const CACHE_SECS: u64 = 30;
+
+pub const SPEC: Spec = Spec {
+    about: "Print paths in your storage starting with `path`, used by shell completions",
+    args: &[Arg::pos("path").kind(Kind::Path).optional()],
+    login: false,
+    examples: &["storage __complete-path /tex/pa"],
+    related: &["completions"],
+    run: complete_path,
+};
+
+#[derive(Serialize, Deserialize)]
+struct CachedDir {
+    at: u64,
+    /// Directories end with `/`.
+    names: Vec<String>,
+}
+
+pub fn complete_path(map: Args) -> Result<Output, Box<dyn Error>> {
+    // completions run on every tab press, so any failure is just nothing to list
+    let paths = candidates(&map).unwrap_or_else(|e| {
+        debug!("No paths to complete: {e}");
+        Vec::new()
+    });
+
+    Ok(Output::new("Finished")
+        .text(paths.join("\n"))
+        .field("paths", paths))
+}
+
+fn candidates(map: &Args) -> Result<Vec<String>, Box<dyn Error>> {
+    if !map.logged_in() {
+        return Err(CError::NotLoggedIn.into());
+    }
+
+    let (dir, prefix) = split(map.str("path"));
+
+    let key = format!("{}:{}:{dir}", map.str("instance"), map.str("id"));
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let mut cache: HashMap<String, CachedDir> = fs::read(cache_path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+    cache.retain(|_, cached| now.saturating_sub(cached.at) < CACHE_SECS);
+
+    let names = match cache.get(&key) {
+        Some(cached) => &cached.names,
+        None => {
+            let names = map
+                .client()?
+                .diritems(&dir)?
+                .into_iter()
+                .map(|item| {
+                    if item.is_file {
+                        item.name
+                    } else {
+                        format!("{}/", item.name)
+                    }
+                })
+                .collect();
+            cache.insert(key.clone(), CachedDir { at: now, names });
+            fs::write(cache_path(), serde_json::to_vec(&cache)?)?;
+            &cache[&key].names
+        }
+    };
+
+    Ok(names
+        .iter()
+        .filter(|name| name.starts_with(&prefix))
+        .map(|name| format!("{dir}{name}"))
+        .collect())
+}
+
+/// Splits a typed path into the directory to list, ending with `/`, and the start of the name.
+fn split(typed: &str) -> (String, String) {
+    let typed = if typed.starts_with('/') {
+        typed.to_string()
+    } else {
+        format!("/{typed}")
+    };
+    let (dir, prefix) = typed.rsplit_once('/').unwrap();
+    (format!("{dir}/"), prefix.to_string())
+}
+
+fn cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap()
+        .join(env!("CARGO_PKG_NAME"))
+        .join("completions.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_dir_and_prefix() {
+        assert_eq!(split("/tex/pa"), ("/tex/".into(), "pa".into()));
+        assert_eq!(split("/tex/"), ("/tex/".into(), "".into()));
+        assert_eq!(split("tex/pa"), ("/tex/".into(), "pa".into()));
+        assert_eq!(split("tex"), ("/".into(), "tex".into()));
+    }
+
+    #[test]
+    fn no_account_completes_nothing() {
+        let output = complete_path(Args::new(&SPEC, HashMap::new())).unwrap();
+        assert_eq!(output.text.as_deref(), Some(""));
+        assert_eq!(output.value["paths"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn root_and_empty_list_the_root() {
+        assert_eq!(split("/"), ("/".into(), "".into()));
+        assert_eq!(split(""), ("/".into(), "".into()));
+    }
+}
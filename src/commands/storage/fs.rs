@@ -1,32 +1,31 @@
 use std::error::Error;
-use std::{collections::HashMap, path::PathBuf};
+use std::path::PathBuf;
 
-use crate::error::Error as CError;
-use crate::functions::{args_parse, prompt_cmd, resolve_path};
-use crate::{
-    config::AccountConfig,
-    functions::{map_args, prompt_not_present},
-};
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::functions::{prompt_cmd, resolve_path};
+use crate::output::Output;
+use crate::Command;
 use log::*;
 
 use super::commands;
 
-const ARGS: &[&str] = &["path"];
-
-pub fn fs(mut map: HashMap<String, String>, args: Vec<String>) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You must be logged in to view user files");
-        return Err(CError::StrErr("not logged in").into());
-    }
-
-    prompt_not_present("Path", "path", &mut map);
+pub const SPEC: Spec = Spec {
+    about: "Start fs repl",
+    args: &[Arg::pos("path").kind(Kind::Path).prompt("Path")],
+    login: true,
+    examples: &["storage fs /"],
+    related: &["storage ls", "storage cat"],
+    run: fs,
+};
 
+pub fn fs(map: Args) -> Result<Output, Box<dyn Error>> {
+    let mut map = map.into_map();
     let path = map.remove("path").unwrap();
     let mut pathbuf = PathBuf::from(path);
 
     let mut commands = commands();
     let _ = commands.remove("fs").unwrap();
+    let commands = Command::from(commands);
 
     loop {
         if !pathbuf.has_root() {
@@ -34,9 +33,7 @@ pub fn fs(mut map: HashMap<String, String>, args: Vec<String>) -> Result<String,
             info!("Defaulting to `/`");
             pathbuf = PathBuf::from("/");
         }
-        let mut cmd = prompt_cmd();
-        let mut map = map.clone();
-        args_parse(&mut cmd, &mut map);
+        let cmd = prompt_cmd()?;
 
         match cmd
             .iter()
@@ -48,26 +45,17 @@ pub fn fs(mut map: HashMap<String, String>, args: Vec<String>) -> Result<String,
             ["cd", path] => pathbuf = resolve_path(&pathbuf.join(path)),
             ["pwd"] => println!("{}", pathbuf.to_str().unwrap()),
             ["exit"] => break,
-            [other, ..] if commands.contains_key(other) => {
+            _ => {
                 let mut map = map.clone();
                 map.insert("prefix".to_string(), pathbuf.display().to_string());
-                let mut cmd = cmd[1..].to_vec();
-                args_parse(&mut cmd, &mut map);
-                run_command(commands.get(other).unwrap()(map, cmd));
+                commands.run(map, cmd);
             }
-            _ => error!("Unknown command"),
         }
     }
 
-    Ok(String::from("Exited"))
+    Ok(Output::new("Exited"))
 }
 
 fn no_root() {
     error!("Invalid path, all paths must start with a root `/`");
 }
-
-fn run_command(res: Result<String, Box<dyn Error>>) {
-    if let Err(e) = res {
-        error!("Error: {e}")
-    }
-}
@@ -1,76 +1,68 @@
+use std::error::Error;
 use std::path::PathBuf;
-use std::{collections::HashMap, error::Error};
 
-use crate::config::AccountConfig;
+use crate::args::{Arg, Args, Kind, Spec};
 use crate::error::Error as CError;
-use crate::functions::{diritem_tostring, get, map_args, prompt_not_present};
+use crate::functions::{diritem_tostring, prompt_not_present};
+use crate::output::Output;
 
-use goodmorning_bindings::services::v1::V1Response;
 use log::*;
 
-const ARGS: &[&str] = &["path"];
+pub const SPEC: Spec = Spec {
+    about: "List directory content",
+    args: &[Arg::pos("path").kind(Kind::Path).optional()],
+    login: true,
+    examples: &["storage ls /", "storage ls /tex"],
+    related: &["storage fs", "storage cat"],
+    run: ls,
+};
 
-pub fn ls(mut map: HashMap<String, String>, args: Vec<String>) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You must be logged in to view user files");
-        return Err(CError::StrErr("not logged in").into());
-    }
-
-    if !map.contains_key("prefix") {
-        prompt_not_present("Path", "path", &mut map)
-    } else if !map.contains_key("path") {
-        let _ = map.insert("path".to_string(), String::new());
+pub fn ls(mut map: Args) -> Result<Output, Box<dyn Error>> {
+    if !map.flag("prefix") {
+        prompt_not_present("Path", "path", &mut map)?;
+    } else if !map.flag("path") {
+        map.set("path", String::new());
     };
 
-    let prefix = PathBuf::from(map.get("prefix").unwrap_or(&String::new()));
-    let path = prefix.join(map.get("path").unwrap());
+    let prefix = PathBuf::from(map.str("prefix"));
+    let path = prefix.join(map.str("path"));
 
     if !path.has_root() {
         error!("User file paths must start with root `/`");
-        return Err(CError::StrErr("invalid file path").into());
+        return Err(CError::Usage("invalid file path".to_string()).into());
     }
 
     let path = path.to_str().unwrap().to_string();
 
-    let instance = map.get("instance").unwrap();
-    let token = map.get("token").unwrap();
-
-    let url = format!("{instance}/api/storage/v1/diritems/{token}/{}", &path[1..]);
+    let mut items = map
+        .client()?
+        .diritems(&path)
+        .inspect_err(|_| error!("Unable to display file content"))?;
 
-    let res: V1Response = get(&url, map.contains_key("http"))?;
-
-    match res {
-        V1Response::DirContent { mut content } => {
-            let longest_size = if content.is_empty() {
-                0
-            } else {
-                content.sort_by(|this, other| this.name.cmp(&other.name));
-                content
-                    .iter()
-                    .max_by(|this, other| this.size.cmp(&other.size))
-                    .unwrap()
-                    .size
-                    .to_string()
-                    .len()
-            };
+    let longest_size = if items.is_empty() {
+        0
+    } else {
+        items.sort_by(|this, other| this.name.cmp(&other.name));
+        items
+            .iter()
+            .max_by(|this, other| this.size.cmp(&other.size))
+            .unwrap()
+            .size
+            .to_string()
+            .len()
+    };
 
-            println!("---");
-            println!("{} items", content.len());
-            content.iter().for_each(|item| {
-                println!(
-                    "{}",
-                    diritem_tostring(item, longest_size, &PathBuf::from(&path))
-                )
-            });
-            println!("---");
-        }
-        V1Response::Error { kind } => {
-            error!("Unable to display file content");
-            return Err(kind.into());
-        }
-        _ => unreachable!(),
-    }
+    let mut lines = vec!["---".to_string(), format!("{} items", items.len())];
+    lines.extend(
+        items
+            .iter()
+            .map(|item| diritem_tostring(item, longest_size, &PathBuf::from(&path))),
+    );
+    lines.push("---".to_string());
+    let text = lines.join("\n");
 
-    Ok(String::from("Finished"))
+    Ok(Output::new("Finished")
+        .text(text)
+        .field("path", &path)
+        .field("items", items))
 }
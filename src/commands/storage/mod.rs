@@ -1,29 +1,35 @@
 use std::collections::HashMap;
 
-use crate::traits::types::CommandFnType;
+use crate::args::Spec;
 
 mod cat;
+mod complete_path;
 mod cp;
 mod fs;
 mod ls;
 mod mkdir;
 mod mv;
+mod pack;
 mod rm;
 mod touch;
+mod unpack;
 mod upload;
 mod vis;
 
-pub fn commands() -> HashMap<&'static str, CommandFnType> {
+pub fn commands() -> HashMap<&'static str, &'static Spec> {
     HashMap::from([
-        ("ls", Box::new(ls::ls) as CommandFnType),
-        ("cat", Box::new(cat::cat) as CommandFnType),
-        ("upload", Box::new(upload::upload) as CommandFnType),
-        ("cp", Box::new(cp::cp) as CommandFnType),
-        ("mkdir", Box::new(mkdir::mkdir) as CommandFnType),
-        ("rm", Box::new(rm::rm) as CommandFnType),
-        ("touch", Box::new(touch::touch) as CommandFnType),
-        ("vis", Box::new(vis::vis) as CommandFnType),
-        ("mv", Box::new(mv::mv) as CommandFnType),
-        ("fs", Box::new(fs::fs) as CommandFnType),
+        ("ls", &ls::SPEC),
+        ("cat", &cat::SPEC),
+        ("upload", &upload::SPEC),
+        ("cp", &cp::SPEC),
+        ("mkdir", &mkdir::SPEC),
+        ("rm", &rm::SPEC),
+        ("touch", &touch::SPEC),
+        ("vis", &vis::SPEC),
+        ("mv", &mv::SPEC),
+        ("fs", &fs::SPEC),
+        ("pack", &pack::SPEC),
+        ("unpack", &unpack::SPEC),
+        ("__complete-path", &complete_path::SPEC),
     ])
 }
@@ -1,75 +1,61 @@
-use std::collections::HashMap;
 use std::error::Error;
 use std::path::PathBuf;
 
-use goodmorning_bindings::services::v1::{V1FromTo, V1Response};
 use log::*;
 
-use crate::config::AccountConfig;
+use crate::args::{Arg, Args, Kind, Spec};
 use crate::error::Error as CError;
-use crate::functions::{map_args, post, prompt_not_present};
-
-const ARGS: &[&str] = &["from", "to"];
-
-pub fn mv(mut map: HashMap<String, String>, args: Vec<String>) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You are not logged in");
-        return Err(CError::StrErr("Not logged in").into());
-    }
-
-    prompt_not_present("From", "from", &mut map);
-    prompt_not_present("To", "to", &mut map);
-
-    let instance = map.get("instance").unwrap();
-    let url = format!(
-        "{}/api/storage/v1/{}",
-        instance,
-        if map.contains_key("overwrite") {
-            "move-overwrite"
-        } else {
-            "move"
-        }
-    );
-
-    let prefix = PathBuf::from(map.get("prefix").unwrap_or(&String::new()));
-    let from = if map.get("user").is_none() {
-        prefix.join(map.get("from").unwrap())
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Moves item",
+    args: &[
+        Arg::pos("from").kind(Kind::Path).prompt("From"),
+        Arg::pos("to").kind(Kind::Path).prompt("To"),
+        Arg::opt("user")
+            .kind(Kind::Int)
+            .help("ID of the user to move from"),
+        Arg::flag("overwrite").help("Replace the destination if it exists"),
+    ],
+    login: true,
+    examples: &[
+        "storage mv /a.txt /archive/a.txt",
+        "storage mv /a.txt /b.txt --overwrite",
+    ],
+    related: &["storage cp", "storage rm"],
+    run: mv,
+};
+
+pub fn mv(map: Args) -> Result<Output, Box<dyn Error>> {
+    let prefix = PathBuf::from(map.str("prefix"));
+    let from = if !map.flag("user") {
+        prefix.join(map.str("from"))
     } else {
-        PathBuf::from(map.get("from").unwrap())
+        PathBuf::from(map.str("from"))
     };
-    let to = prefix.join(map.get("to").unwrap());
+    let to = prefix.join(map.str("to"));
 
     if !from.has_root() {
         error!("User file paths must start with root `/`");
-        return Err(CError::StrErr("invalid file path").into());
+        return Err(CError::Usage("invalid file path".to_string()).into());
     }
 
     let from = from.to_str().unwrap().to_string();
     let to = to.to_str().unwrap().to_string();
-    let from_user = map.get("user").unwrap_or(map.get("id").unwrap());
-    let token = map.get("token").unwrap().to_string();
-
-    let body = V1FromTo {
-        from,
-        to: to.clone(),
-        from_userid: from_user.parse()?,
-        token,
+    let client = map.client()?;
+    let from_user = match map.opt("user") {
+        Some(_) => map.int("user")?,
+        None => client.id,
     };
 
-    let res = post(&url, body, map.contains_key("http"))?;
-
-    match res {
-        V1Response::Error { kind } => {
-            error!("File not moved");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::Moved => {
-            info!("Item move successfully");
-            info!("The new path is `{to}`");
-        }
-        _ => unreachable!(),
-    }
+    client
+        .move_item(&from, &to, from_user, map.flag("overwrite"))
+        .inspect_err(|_| error!("File not moved"))?;
+    info!("Item move successfully");
+    info!("The new path is `{to}`");
 
-    Ok(String::from("Moved"))
+    Ok(Output::new("Moved")
+        .field("from", from)
+        .field("to", to)
+        .field("user", from_user))
 }
@@ -0,0 +1,111 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::*;
+use tar::{Builder, EntryType, Header};
+
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::error::Error as CError;
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Download a directory into a .tar.gz archive",
+    args: &[
+        Arg::pos("path").kind(Kind::Path).prompt("Remote directory"),
+        Arg::pos("out")
+            .kind(Kind::File)
+            .prompt("Archive path (e.g. out.tar.gz)"),
+        Arg::flag("overwrite").help("Replace the archive if it exists"),
+    ],
+    login: true,
+    examples: &["storage pack /tex tex.tar.gz"],
+    related: &["storage unpack"],
+    run: pack,
+};
+
+pub fn pack(map: Args) -> Result<Output, Box<dyn Error>> {
+    let prefix = PathBuf::from(map.str("prefix"));
+    let root = prefix.join(map.str("path"));
+
+    if !root.has_root() {
+        error!("User file paths must start with root `/`");
+        return Err(CError::Usage("invalid file path".to_string()).into());
+    }
+
+    let client = map.client()?;
+    let out = PathBuf::from(map.str("out"));
+
+    if out.exists() && !map.flag("overwrite") {
+        error!("`{}` already exists", out.display());
+        info!("Add the `--overwrite` flag to replace it");
+        return Err(CError::Usage("archive already exists".to_string()).into());
+    }
+
+    // written next to `out` and moved into place once complete, so a failed download
+    // neither leaves a broken archive nor replaces a good one
+    let partial = Partial(out.with_file_name(format!(
+        ".{}.{}.part",
+        out.file_name().unwrap_or_default().to_string_lossy(),
+        process::id()
+    )));
+    let file = File::create(&partial.0)?;
+    let mut archive = Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let mut files = 0;
+    let mut stack = vec![PathBuf::new()];
+    while let Some(relative) = stack.pop() {
+        let remote = root.join(&relative);
+        let remote = remote.to_str().unwrap();
+        debug!("Listing `{remote}`");
+
+        for item in client.diritems(remote)? {
+            let relative = relative.join(&item.name);
+            let mut header = Header::new_gnu();
+            header.set_mtime(item.last_modified);
+
+            if item.is_file {
+                let bytes = client.file(root.join(&relative).to_str().unwrap())?;
+                header.set_entry_type(EntryType::Regular);
+                header.set_mode(0o644);
+                header.set_size(bytes.len() as u64);
+                archive.append_data(&mut header, &relative, &bytes[..])?;
+                files += 1;
+                info!("Packed `{}`", display_path(&relative));
+            } else {
+                header.set_entry_type(EntryType::Directory);
+                header.set_mode(0o755);
+                header.set_size(0);
+                archive.append_data(&mut header, &relative, &[][..])?;
+                stack.push(relative);
+            }
+        }
+    }
+
+    archive.into_inner()?.finish()?;
+    fs::rename(&partial.0, &out)?;
+    info!("{files} files packed into `{}`", out.display());
+
+    Ok(Output::new("Packed")
+        .field("path", root)
+        .field("archive", out)
+        .field("files", files))
+}
+
+/// An archive being written, removed unless it was moved into place.
+struct Partial(PathBuf);
+
+impl Drop for Partial {
+    fn drop(&mut self) {
+        if fs::remove_file(&self.0).is_ok() {
+            debug!("Removed unfinished archive `{}`", self.0.display());
+        }
+    }
+}
+
+fn display_path(path: &Path) -> String {
+    format!("/{}", path.to_str().unwrap())
+}
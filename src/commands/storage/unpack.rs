@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use goodmorning_bindings::services::v1::V1Error;
+use log::*;
+use tar::{Archive, EntryType};
+
+use crate::api::GmClient;
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::error::Error as CError;
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Upload a .tar.gz archive into a directory",
+    args: &[
+        Arg::pos("archive").kind(Kind::File).prompt("Archive path"),
+        Arg::pos("path").kind(Kind::Path).prompt("Remote directory"),
+        Arg::flag("overwrite").help("Replace files that already exist"),
+    ],
+    login: true,
+    examples: &[
+        "storage unpack tex.tar.gz /tex",
+        "storage unpack backup.tar.gz / --overwrite",
+    ],
+    related: &["storage pack"],
+    run: unpack,
+};
+
+pub fn unpack(map: Args) -> Result<Output, Box<dyn Error>> {
+    let prefix = PathBuf::from(map.str("prefix"));
+    let root = prefix.join(map.str("path"));
+
+    if !root.has_root() {
+        error!("User file paths must start with root `/`");
+        return Err(CError::Usage("invalid file path".to_string()).into());
+    }
+
+    let client = map.client()?;
+    let overwrite = map.flag("overwrite");
+    let file = PathBuf::from(map.str("archive"));
+
+    if !file.exists() {
+        error!("Archive doesn't seem to exist");
+        return Err(CError::io(&file, io::ErrorKind::NotFound.into()).into());
+    }
+
+    let file = OpenOptions::new().read(true).open(&file)?;
+    let mut archive = Archive::new(GzDecoder::new(file));
+
+    let mut created = HashSet::new();
+    let mut files = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let relative = entry.path()?.to_path_buf();
+
+        if relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            warn!(
+                "Skipping `{}`, entry escapes the archive",
+                relative.display()
+            );
+            continue;
+        }
+
+        match entry.header().entry_type() {
+            EntryType::Directory => {
+                ensure_dir(&client, &root, &relative, &mut created)?;
+            }
+            EntryType::Regular | EntryType::Continuous => {
+                if let Some(parent) = relative.parent() {
+                    ensure_dir(&client, &root, parent, &mut created)?;
+                }
+
+                let mut buffer = Vec::new();
+                entry.read_to_end(&mut buffer)?;
+                let path = root.join(&relative);
+                let path = path.to_str().unwrap();
+
+                match client.upload(path, &buffer, overwrite) {
+                    Ok(()) => {
+                        files += 1;
+                        info!("Uploaded `{path}`");
+                    }
+                    Err(e) => {
+                        error!("Failed to upload `{path}`");
+                        if let CError::Server {
+                            kind: V1Error::FileTypeMismatch { expected, got },
+                            ..
+                        } = &e
+                        {
+                            error!("Expected `{expected}`, got `{got}` at `{path}`");
+                        } else if !overwrite {
+                            info!(
+                                "If the file already exists, try again with the `--overwrite` flag"
+                            );
+                        }
+                        return Err(e.into());
+                    }
+                }
+            }
+            other => warn!(
+                "Skipping `{}`, unsupported entry type {other:?}",
+                relative.display()
+            ),
+        }
+    }
+
+    info!("{files} files unpacked into `{}`", root.display());
+
+    Ok(Output::new("Unpacked")
+        .field("path", root)
+        .field("files", files))
+}
+
+/// Creates `relative` and all of its ancestors under `root`, each remote directory only once.
+fn ensure_dir(
+    client: &GmClient,
+    root: &Path,
+    relative: &Path,
+    created: &mut HashSet<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let mut current = root.to_path_buf();
+    let mut dirs = vec![current.clone()];
+    for component in relative.components() {
+        if let Component::Normal(name) = component {
+            current.push(name);
+            dirs.push(current.clone());
+        }
+    }
+
+    for dir in dirs {
+        if created.contains(&dir) {
+            continue;
+        }
+
+        if let Err(e) = client.mkdir(dir.to_str().unwrap()) {
+            if !is_dir(client, &dir) {
+                return Err(e.into());
+            }
+            debug!("`{}` already exists", dir.display());
+        }
+        created.insert(dir);
+    }
+
+    Ok(())
+}
+
+/// Whether `dir` is an existing remote directory, the root always is.
+fn is_dir(client: &GmClient, dir: &Path) -> bool {
+    let (Some(parent), Some(name)) = (dir.parent(), dir.file_name()) else {
+        return true;
+    };
+
+    client
+        .diritems(parent.to_str().unwrap())
+        .is_ok_and(|items| {
+            items
+                .iter()
+                .any(|item| !item.is_file && name == item.name.as_str())
+        })
+}
@@ -1,48 +1,52 @@
+use std::error::Error;
 use std::fs::OpenOptions;
-use std::io::Read;
+use std::io::{self, Read};
 use std::path::PathBuf;
-use std::{collections::HashMap, error::Error};
 
-use goodmorning_bindings::services::v1::{V1Error, V1Response};
+use goodmorning_bindings::services::v1::V1Error;
 use log::*;
-use reqwest::blocking::multipart::{Form, Part};
+use sha2::{Digest, Sha256};
 
-use crate::config::AccountConfig;
+use crate::args::{Arg, Args, Kind, Spec};
 use crate::error::Error as CError;
-use crate::functions::{map_args, prompt_not_present};
-
-const ARGS: &[&str] = &["file", "path"];
-
-pub fn upload(
-    mut map: HashMap<String, String>,
-    args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You must be logged in to view user files");
-        return Err(CError::StrErr("not logged in").into());
-    }
-
-    prompt_not_present("File path", "file", &mut map);
-    prompt_not_present("Destination path", "path", &mut map);
-
-    let instance = map.get("instance").unwrap();
-    let token = map.get("token").unwrap();
-    let file = PathBuf::from(map.get("file").unwrap());
-    let prefix = PathBuf::from(map.get("prefix").unwrap_or(&String::new()));
-    let path = prefix.join(map.get("path").unwrap());
-    dbg!(&path);
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Uploads a file",
+    args: &[
+        Arg::pos("file").kind(Kind::File).prompt("File path"),
+        Arg::pos("path").kind(Kind::Path).prompt("Destination path"),
+        Arg::flag("overwrite").help("Replace the file if it exists"),
+        Arg::flag("verify").help("Re-download and check SHA-256, retrying on mismatch"),
+        Arg::opt("retries")
+            .kind(Kind::Uint)
+            .default("3")
+            .help("Attempts for `--verify`"),
+    ],
+    login: true,
+    examples: &[
+        "storage upload paper.tex /tex/paper.tex",
+        "storage upload data.csv /data.csv --overwrite --verify",
+    ],
+    related: &["storage cat", "storage pack"],
+    run: upload,
+};
+
+pub fn upload(map: Args) -> Result<Output, Box<dyn Error>> {
+    let file = PathBuf::from(map.str("file"));
+    let prefix = PathBuf::from(map.str("prefix"));
+    let path = prefix.join(map.str("path"));
 
     if !path.has_root() {
         error!("User file paths must start with root `/`");
-        return Err(CError::StrErr("invalid file path").into());
+        return Err(CError::Usage("invalid file path".to_string()).into());
     }
 
     let path = path.to_str().unwrap().to_string();
 
     if !file.exists() {
         error!("File to upload doesn't seem to exist");
-        return Err(CError::StrErr("file not found").into());
+        return Err(CError::io(&file, io::ErrorKind::NotFound.into()).into());
     }
 
     let mut file = OpenOptions::new().read(true).open(&file)?;
@@ -50,57 +54,72 @@ pub fn upload(
     let mut buffer = Vec::new();
     file.read_to_end(&mut buffer)?;
 
-    let form = Form::new().part(
-        "file",
-        Part::bytes(buffer)
-            .file_name("filename.ext")
-            .mime_str("application/octet-stream")?,
-    );
-
-    let overwrite = map.contains_key("overwrite");
-
-    let url = format!(
-        "{}://{instance}/api/storage/v1/{}/{token}/{}",
-        if map.contains_key("http") {
-            "http"
-        } else {
-            "https"
-        },
-        if overwrite {
-            "upload-overwrite"
-        } else {
-            "upload"
-        },
-        &path[1..]
-    );
-
-    info!("Sending request and uploading file");
-    let res: V1Response = reqwest::blocking::Client::new()
-        .post(url)
-        .multipart(form)
-        .send()?
-        .json()?;
-
-    match res {
-        V1Response::FileItemCreated => {
-            info!("File item successfully created at `{path}`");
+    let overwrite = map.flag("overwrite");
+    let verify = map.flag("verify");
+    let retries = map.uint("retries")?;
+    let client = map.client()?;
+    let hash = sha256(&buffer);
+
+    let mut attempt = 0;
+    loop {
+        // once the first attempt went through, the path is occupied by the bad copy
+        let overwrite = overwrite || attempt > 0;
+        match client.upload(&path, &buffer, overwrite) {
+            Ok(()) => {
+                info!("File item successfully created at `{path}`");
+            }
+            Err(e) => {
+                match &e {
+                    CError::Server {
+                        kind: V1Error::FileNotFound,
+                        ..
+                    } if overwrite => {
+                        error!("File not found");
+                        info!("Perhaps this means the file path is not occupied, and you should not include the `--overwrite` flag");
+                    }
+                    CError::Server {
+                        kind: V1Error::FileTypeMismatch { expected, got },
+                        ..
+                    } => error!("Expected `{expected}`, got `{got}`"),
+                    _ => {}
+                }
+                return Err(e.into());
+            }
+        }
+
+        if !verify {
+            break;
         }
-        V1Response::Error {
-            kind: V1Error::FileNotFound,
-        } if overwrite => {
-            error!("File not found");
-            info!("Perhaps this means the file path is not occupied, and you should not include the `--overwrite` flag");
-            return Err(CError::StrErr("Upload failed").into());
+
+        info!("Fetching uploaded file for verification");
+        let remote_hash = sha256(&client.file(&path)?);
+        if remote_hash == hash {
+            info!("Verified, SHA-256 is `{hash}`");
+            break;
         }
-        V1Response::Error { kind } => {
-            if let V1Error::FileTypeMismatch { expected, got } = kind {
-                error!("Expected `{expected}`, got `{got}`");
-                return Err(CError::StrErr("Upload failed").into());
+
+        warn!("Checksum mismatch, expected `{hash}`, got `{remote_hash}`");
+        if attempt >= retries {
+            error!("Upload still corrupted after {retries} retries");
+            return Err(CError::Checksum {
+                path,
+                expected: hash,
+                got: remote_hash,
             }
-            return Err(kind.into());
+            .into());
         }
-        _ => unreachable!(),
+
+        attempt += 1;
+        info!("Retrying upload ({attempt}/{retries})");
     }
 
-    Ok("Uploaded".to_string())
+    Ok(Output::new("Uploaded")
+        .field("path", path)
+        .field("sha256", hash)
+        .field("verified", verify)
+        .field("retries", attempt))
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
 }
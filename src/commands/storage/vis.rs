@@ -1,86 +1,61 @@
-use std::collections::HashMap;
 use std::error::Error;
 use std::path::PathBuf;
 
-use goodmorning_bindings::services::v1::{
-    ItemVisibility, V1PathOnly, V1PathVisibility, V1Response,
-};
+use goodmorning_bindings::services::v1::ItemVisibility;
 use log::*;
 
-use crate::config::AccountConfig;
+use crate::args::{Arg, Args, Kind, Spec};
 use crate::error::Error as CError;
-use crate::functions::{map_args, post, prompt_not_present};
-
-const ARGS: &[&str] = &["path", "vis"];
-
-pub fn vis(mut map: HashMap<String, String>, args: Vec<String>) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You are not logged in");
-        return Err(CError::StrErr("Not logged in").into());
-    }
-
-    prompt_not_present("Path", "path", &mut map);
-    prompt_not_present(
-        "Visibility (private, public, hidden, inherit)",
-        "vis",
-        &mut map,
-    );
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Change item visibility",
+    args: &[
+        Arg::pos("path").kind(Kind::Path).prompt("Path"),
+        Arg::pos("vis")
+            .kind(Kind::Choice(&["private", "public", "hidden", "inherit"]))
+            .prompt("Visibility (private, public, hidden, inherit)"),
+    ],
+    login: true,
+    examples: &["storage vis /tex/paper.pdf public"],
+    related: &["storage ls"],
+    run: vis,
+};
 
-    let instance = map.get("instance").unwrap();
-    let vis_str = map.get("vis").unwrap();
-    let prefix = PathBuf::from(map.get("prefix").unwrap_or(&String::new()));
-    let path = prefix.join(map.get("path").unwrap());
+pub fn vis(map: Args) -> Result<Output, Box<dyn Error>> {
+    let vis_str = map.str("vis");
+    let prefix = PathBuf::from(map.str("prefix"));
+    let path = prefix.join(map.str("path"));
 
     if !path.has_root() {
         error!("User file paths must start with root `/`");
-        return Err(CError::StrErr("invalid file path").into());
+        return Err(CError::Usage("invalid file path".to_string()).into());
     }
 
     let path = path.to_str().unwrap().to_string();
-    let token = map.get("token").unwrap().to_string();
-
-    let vis = match vis_str.as_str() {
-        "private" => ItemVisibility::Private,
-        "public" => ItemVisibility::Public,
-        "hidden" => ItemVisibility::Hidden,
-        "inherit" => ItemVisibility::Private, // placeholder
-        _ => return Err(CError::StringErr(format!("Invalid option `{vis_str}`")).into()),
+    let vis = match vis_str {
+        "private" => Some(ItemVisibility::Private),
+        "public" => Some(ItemVisibility::Public),
+        "hidden" => Some(ItemVisibility::Hidden),
+        "inherit" => None,
+        _ => return Err(CError::Usage(format!("Invalid option `{vis_str}`")).into()),
     };
 
-    let res = if vis_str.as_str() == "inherit" {
-        let url = format!("{}/api/storage/v1/remove-visibility", instance,);
-
-        let body = V1PathOnly { path, token };
-
-        post(&url, body, map.contains_key("http"))?
-    } else {
-        let body = V1PathVisibility {
-            path,
-            visibility: vis,
-            token,
-        };
-
-        let url = format!("{}/api/storage/v1/set-visibility", instance,);
-        post(&url, body, map.contains_key("http"))?
-    };
+    let client = map.client()?;
+    let changed = match vis {
+        Some(vis) => client.set_visibility(&path, vis),
+        None => client.remove_visibility(&path),
+    }
+    .inspect_err(|_| error!("Visibility not changed"))?;
 
-    match res {
-        V1Response::Error { kind } => {
-            error!("Visibility not changed");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::VisibilityChanged if vis_str.as_str() == "inherit" => {
-            info!("Visibility reset");
-        }
-        V1Response::VisibilityChanged => {
-            info!("Visibility changed to `{vis:?}`");
-        }
-        V1Response::NothingChanged => {
-            warn!("Nothing changed");
-        }
-        _ => unreachable!(),
+    match vis {
+        _ if !changed => warn!("Nothing changed"),
+        Some(vis) => info!("Visibility changed to `{vis:?}`"),
+        None => info!("Visibility reset"),
     }
 
-    Ok(String::from("Finished"))
+    Ok(Output::new("Finished")
+        .field("path", path)
+        .field("visibility", vis_str)
+        .field("changed", changed))
 }
@@ -1,113 +1,92 @@
-use std::any::Any;
+use std::error::Error;
 use std::path::PathBuf;
-use std::{collections::HashMap, error::Error};
 
-use crate::config::AccountConfig;
+use crate::args::{Arg, Args, Kind, Spec};
 use crate::error::Error as CError;
-use crate::functions::{map_args, post, prompt_not_present};
+use crate::output::Output;
 
-use goodmorning_bindings::services::v1::{
-    Compiler, FromFormat, ToFormat, V1Compile, V1Error, V1Response,
-};
-use goodmorning_bindings::structs::TexCompileRes;
+use goodmorning_bindings::services::v1::{Compiler, FromFormat, ToFormat, V1Error};
 use log::*;
 
-const ARGS: &[&str] = &["path", "from", "to", "compiler"];
-
-pub fn compile(
-    mut map: HashMap<String, String>,
-    args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You must be logged in to view user files");
-        return Err(CError::StrErr("not logged in").into());
-    }
-
-    prompt_not_present("Path (`/tex` omitted)", "path", &mut map);
-    prompt_not_present("From format", "from", &mut map);
-    prompt_not_present("To format", "to", &mut map);
+pub const SPEC: Spec = Spec {
+    about: "Compiles between formats",
+    args: &[
+        Arg::pos("path").prompt("Path (`/tex` omitted)"),
+        Arg::pos("from")
+            .kind(Kind::Choice(&["markdown", "md", "latex"]))
+            .prompt("From format"),
+        Arg::pos("to")
+            .kind(Kind::Choice(&["html", "pdf"]))
+            .prompt("To format"),
+        Arg::pos("compiler")
+            .prompt("Compiler (leave blank for default)")
+            .help("`pulldown` (md to html) or `pdflatex` (latex to pdf)"),
+    ],
+    login: true,
+    examples: &[
+        "tex compile /paper.tex latex pdf ''",
+        "tex compile /notes.md md html pulldown",
+    ],
+    related: &["jobs list", "tex publish"],
+    run: compile,
+};
 
-    let path = PathBuf::from(map.get("path").unwrap());
+pub fn compile(map: Args) -> Result<Output, Box<dyn Error>> {
+    let path = PathBuf::from(map.str("path"));
     if !path.has_root() {
         error!("User file paths must start with root `/`");
-        return Err(CError::StrErr("invalid file path").into());
+        return Err(CError::Usage("invalid file path".to_string()).into());
     }
 
     let path = path.to_str().unwrap().to_string();
 
-    let instance = map.get("instance").unwrap();
-    let token = map.get("token").unwrap().to_string();
-
-    let url = format!("{instance}/api/compile/v1/simple");
-
-    let from = match map.get("from").unwrap().as_str() {
+    let from = match map.str("from") {
         "md" | "markdown" => FromFormat::Markdown,
         "latex" => FromFormat::Latex,
         _ => {
             error!("Valid formats are: `markdown` and `latex`");
-            return Err(CError::StrErr("invalid from format").into());
+            return Err(CError::Usage("invalid from format".to_string()).into());
         }
     };
-    let to = match map.get("to").unwrap().as_str() {
+    let to = match map.str("to") {
         "html" => ToFormat::Html,
         "pdf" => ToFormat::Pdf,
         _ => {
             error!("Valid formats are: `html`");
-            return Err(CError::StrErr("invalid to format").into());
+            return Err(CError::Usage("invalid to format".to_string()).into());
         }
     };
-    prompt_not_present("Compiler (leave blank for default)", "compiler", &mut map);
 
-    let body = V1Compile {
-        path,
-        token,
-        from,
-        to,
-        compiler: match map.get("compiler").unwrap().as_str() {
-            "" => None,
-            "pulldown" | "pulldown-cmark" => Some(Compiler::PulldownCmark),
-            "pdflatex" => Some(Compiler::Pdflatex),
-            _ => {
-                error!(
-                    "Unknow compiler, accept `pulldown` (md to html) and `pdflatex` (latex to pdf)"
-                );
-                return Err(CError::StrErr("Invalid compiler").into());
-            }
-        },
+    let compiler = match map.str("compiler") {
+        "" => None,
+        "pulldown" | "pulldown-cmark" => Some(Compiler::PulldownCmark),
+        "pdflatex" => Some(Compiler::Pdflatex),
+        _ => {
+            error!("Unknow compiler, accept `pulldown` (md to html) and `pdflatex` (latex to pdf)");
+            return Err(CError::Usage("Invalid compiler".to_string()).into());
+        }
     };
 
-    let res: V1Response = post(&url, body, map.contains_key("http"))?;
-
-    match res {
-        // V1Response::Compiled { id, newpath } => {
-        //     info!("Compile success");
-        //     info!("New path: {newpath}");
-        //     info!("Job ID: {id}");
-        // }
-        V1Response::Any { value } => {
-            let value: Box<dyn Any> = value;
-            let res = value.downcast_ref::<TexCompileRes>().unwrap();
-            info!("Compile success");
-            info!("New path: {}", res.newpath);
-            info!("Job ID: {}", res.id);
-        }
-        V1Response::TexCompiled { id, newpath } => {
-            info!("Compile success");
-            info!("New path: {newpath}");
-            info!("Job ID: {id}");
-        }
-        V1Response::Error {
+    let (id, newpath) = match map.client()?.compile(&path, from, to, compiler) {
+        Ok(compiled) => compiled,
+        Err(CError::Server {
             kind: V1Error::CompileError { content },
-        } => {
+            endpoint,
+        }) => {
             error!("Error compiling:\n{content}");
-            return Err(CError::StrErr("Not compiled").into());
-        }
-        V1Response::Error { kind } => {
-            return Err(kind.into());
+            return Err(CError::Server {
+                kind: V1Error::CompileError { content },
+                endpoint,
+            }
+            .into());
         }
-        _ => unreachable!(),
-    }
+        Err(e) => return Err(e.into()),
+    };
+    info!("Compile success");
+    info!("New path: {newpath}");
+    info!("Job ID: {id}");
 
-    Ok(String::from("Finished"))
+    Ok(Output::new("Finished")
+        .field("id", id)
+        .field("newpath", newpath))
 }
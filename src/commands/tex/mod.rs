@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::traits::types::CommandFnType;
+use crate::args::Spec;
 
 mod compile;
 mod pfpedit;
@@ -9,16 +9,13 @@ mod publish;
 mod publishes;
 mod set_profile;
 
-pub fn commands() -> HashMap<&'static str, CommandFnType> {
+pub fn commands() -> HashMap<&'static str, &'static Spec> {
     HashMap::from([
-        ("tprofile", Box::new(profile::profile) as CommandFnType),
-        (
-            "tpfedit",
-            Box::new(set_profile::set_profile) as CommandFnType,
-        ),
-        ("tpfpedit", Box::new(pfpedit::pfpedit) as CommandFnType),
-        ("compile", Box::new(compile::compile) as CommandFnType),
-        ("publish", Box::new(publish::publish) as CommandFnType),
-        ("publishes", Box::new(publishes::publishes) as CommandFnType),
+        ("profile", &profile::SPEC),
+        ("pfedit", &set_profile::SPEC),
+        ("pfpedit", &pfpedit::SPEC),
+        ("compile", &compile::SPEC),
+        ("publish", &publish::SPEC),
+        ("publishes", &publishes::SPEC),
     ])
 }
@@ -1,47 +1,38 @@
-use std::{collections::HashMap, error::Error};
+use std::error::Error;
 
-use goodmorning_bindings::services::v1::V1Response;
 use log::*;
 
+use crate::api::GmClient;
+use crate::args::{Arg, Args, Spec};
 use crate::error::Error as CError;
-use crate::functions::{display_profile, get, map_args, prompt_not_present};
+use crate::functions::display_profile;
+use crate::output::Output;
 
-const ARGS: &[&str] = &["username"];
+pub const SPEC: Spec = Spec {
+    about: "View user profile",
+    args: &[Arg::pos("username").prompt("Username (e.g. username:instance.com)")],
+    login: false,
+    examples: &["tex profile alice:gm.example.com"],
+    related: &["tex pfedit", "tex publishes"],
+    run: profile,
+};
 
-pub fn profile(
-    mut map: HashMap<String, String>,
-    args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-
-    prompt_not_present(
-        "Username (e.g. username:instance.com)",
-        "username",
-        &mut map,
-    );
-
-    let username = map.get("username").unwrap();
+pub fn profile(map: Args) -> Result<Output, Box<dyn Error>> {
+    let username = map.str("username");
 
     if !username.contains(':') {
         error!("This does not seem to be a valid username string");
-        return Err(CError::StringErr(format!("Invalid username string `{username}`")).into());
+        return Err(CError::Usage(format!("Invalid username string `{username}`")).into());
     }
     let (username, instance) = username.split_once(':').unwrap();
 
-    let url = format!("{}/api/generic/v1/profile/name/{username}", instance);
+    let (profile, account) = GmClient::new(instance, map.flag("http"))
+        .profile(username)
+        .inspect_err(|_| error!("Cannot load profile"))?;
 
-    let res: V1Response = get(&url, map.contains_key("http"))?;
-
-    match res {
-        V1Response::Error { kind } => {
-            error!("Cannot load profile");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::Profile { profile, account } => {
-            info!("Recieved profile");
-            println!("{}", display_profile(&profile, &account, instance));
-        }
-        _ => unreachable!(),
-    }
-    Ok("Ran".to_string())
+    info!("Recieved profile");
+    Ok(Output::new("Ran")
+        .text(display_profile(&profile, &account, instance))
+        .field("account", account)
+        .field("profile", profile))
 }
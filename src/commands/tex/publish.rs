@@ -1,60 +1,35 @@
+use crate::args::{Arg, Args, Spec};
 use crate::error::Error as CError;
-use crate::functions::{post, prompt_not_present};
-use crate::{config::AccountConfig, functions::map_args};
-use goodmorning_bindings::services::v1::{V1Publish, V1Response};
+use crate::output::Output;
 use log::*;
+use std::error::Error;
 use std::path::PathBuf;
-use std::{collections::HashMap, error::Error};
 
-const ARGS: &[&str] = &["path", "title", "desc"];
-
-pub fn publish(
-    mut map: HashMap<String, String>,
-    args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You must be logged in to view user files");
-        return Err(CError::StrErr("not logged in").into());
-    }
-
-    prompt_not_present("Path (`/tex` omitted)", "path", &mut map);
-    prompt_not_present("Title", "title", &mut map);
-    prompt_not_present("Description", "desc", &mut map);
-
-    let path = PathBuf::from(map.get("path").unwrap());
+pub const SPEC: Spec = Spec {
+    about: "Publish a file",
+    args: &[
+        Arg::pos("path").prompt("Path (`/tex` omitted)"),
+        Arg::pos("title").prompt("Title"),
+        Arg::pos("desc").prompt("Description"),
+    ],
+    login: true,
+    examples: &["tex publish /paper.pdf 'My paper' 'First draft'"],
+    related: &["tex publishes", "tex compile"],
+    run: publish,
+};
+
+pub fn publish(map: Args) -> Result<Output, Box<dyn Error>> {
+    let path = PathBuf::from(map.str("path"));
     if !path.has_root() {
         error!("User file paths must start with root `/`");
-        return Err(CError::StrErr("invalid file path").into());
+        return Err(CError::Usage("invalid file path".to_string()).into());
     }
 
-    let path = path.to_str().unwrap().to_string();
-    let title = map.get("title").unwrap().to_string();
-    let desc = map.get("desc").unwrap().to_string();
-
-    let instance = map.get("instance").unwrap();
-    let token = map.get("token").unwrap().to_string();
-
-    let url = format!("{instance}/api/publish/v1/publish");
-    let body = V1Publish {
-        token,
-        path,
-        title,
-        desc,
-    };
-
-    let res: V1Response = post(&url, body, map.contains_key("http"))?;
-
-    match res {
-        V1Response::Error { kind } => {
-            error!("File not published");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::TexPublished { id } => {
-            info!("Your published item has ID of {id}")
-        }
-        _ => unreachable!(),
-    }
+    let id = map
+        .client()?
+        .publish(path.to_str().unwrap(), map.str("title"), map.str("desc"))
+        .inspect_err(|_| error!("File not published"))?;
 
-    Ok(String::from("Published"))
+    info!("Your published item has ID of {id}");
+    Ok(Output::new("Published").field("id", id))
 }
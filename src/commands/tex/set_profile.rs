@@ -1,65 +1,42 @@
-use std::{collections::HashMap, error::Error};
+use std::error::Error;
 
-use goodmorning_bindings::services::v1::{V1ProfileOnly, V1Response, V1TokenOnly};
 use goodmorning_bindings::structs::{ContactDetail, ProfileCustomisable, ProfileDetail};
 use log::*;
 
-use crate::config::AccountConfig;
-use crate::error::Error as CError;
+use crate::args::{Arg, Args, Spec};
 use crate::functions::{
     contacts_from_string, contacts_list, contacts_prompt, details_from_string, details_list,
-    details_prompt, display_profile_only, get, post, prompt, prompt_cmd, yes_msg,
+    details_prompt, display_profile_only, prompt, prompt_cmd, yes_msg,
+};
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "A repl to change your profile",
+    args: &[Arg::flag("reset").help("Reset your profile")],
+    login: true,
+    examples: &["tex pfedit", "tex pfedit --reset"],
+    related: &["tex pfpedit", "tex profile"],
+    run: set_profile,
 };
 
-pub fn set_profile(
-    map: HashMap<String, String>,
-    _args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    // map_args(&mut map, ARGS, args)?;
-    if !AccountConfig::is_loggedin_map(&map) {
-        error!("You are not logged in");
-        return Err(CError::StrErr("Not logged in").into());
-    }
-
-    let instance = map.get("instance").unwrap();
-    let token = map.get("token").unwrap();
-    let id = map.get("id").unwrap().parse()?;
-
-    if map.contains_key("reset") {
-        yes_msg("Are you sure you want to reset your profile?", &map);
-        let url = format!("{}/api/generic/v1/reset-profile", instance);
-        let body = V1TokenOnly {
-            token: token.to_string(),
-        };
-
-        let res: V1Response = post(&url, body, map.contains_key("http"))?;
-
-        match res {
-            V1Response::Error { kind } => {
-                error!("Cannot load profile");
-                return Err(CError::StringErr(kind.to_string()).into());
-            }
-            V1Response::ProfileUpdated => {
-                info!("Profile reset");
-            }
-            _ => unreachable!(),
-        }
-        return Ok("Ran".to_string());
+pub fn set_profile(map: Args) -> Result<Output, Box<dyn Error>> {
+    let instance = map.str("instance");
+    let client = map.client()?;
+    let id = client.id;
+
+    if map.flag("reset") {
+        yes_msg("Are you sure you want to reset your profile?", &map)?;
+        client
+            .reset_profile()
+            .inspect_err(|_| error!("Cannot load profile"))?;
+        info!("Profile reset");
+        return Ok(Output::new("Ran"));
     }
 
-    let url = format!("{}/api/generic/v1/profile-only/id/{id}", instance);
-
-    let res: V1Response = get(&url, map.contains_key("http"))?;
-
-    let mut profile = match res {
-        V1Response::Error { kind } => {
-            error!("Cannot load profile");
-            info!("Try running again with `--reset` flag");
-            return Err(CError::StringErr(kind.to_string()).into());
-        }
-        V1Response::ProfileOnly { profile } => profile,
-        _ => unreachable!(),
-    };
+    let mut profile = client.profile_only().inspect_err(|_| {
+        error!("Cannot load profile");
+        info!("Try running again with `--reset` flag");
+    })?;
 
     info!("Profile recieved");
 
@@ -74,7 +51,7 @@ pub fn set_profile(
             "{}\nRun `help` to see a list of commands\n\n",
             display_profile_only(&profile, id, instance)
         );
-        let cmd = prompt_cmd();
+        let cmd = prompt_cmd()?;
 
         match cmd
             .iter()
@@ -87,9 +64,9 @@ pub fn set_profile(
             ["detail"] => {
                 let res = (|| -> Result<ProfileDetail, Box<dyn Error>> {
                     println!("{}\n", details_list());
-                    let index: usize = prompt("What do you want to add (1-7)").parse()?;
+                    let index: usize = prompt("What do you want to add (1-7)")?.parse()?;
 
-                    let value = prompt(&details_prompt(index)?);
+                    let value = prompt(&details_prompt(index)?)?;
                     details_from_string(index, value)
                 })();
 
@@ -101,9 +78,9 @@ pub fn set_profile(
             ["contact"] => {
                 let res = (|| -> Result<ContactDetail, Box<dyn Error>> {
                     println!("{}\n", contacts_list());
-                    let index: usize = prompt("What do you want to add (1-13)").parse()?;
+                    let index: usize = prompt("What do you want to add (1-13)")?.parse()?;
 
-                    let value = prompt(&contacts_prompt(index)?);
+                    let value = prompt(&contacts_prompt(index)?)?;
                     contacts_from_string(index, value)
                 })();
                 match res {
@@ -133,25 +110,9 @@ pub fn set_profile(
                     continue;
                 }
 
-                let body = V1ProfileOnly {
-                    profile: profile.clone(),
-                    token: token.clone(),
-                };
-                let url = format!("{}/api/generic/v1/set-profile", instance);
-                let res: V1Response = match post(&url, body, map.contains_key("http")) {
-                    Ok(res) => res,
-                    Err(e) => {
-                        error!("{e}");
-                        continue;
-                    }
-                };
-
-                match res {
-                    V1Response::ProfileUpdated => info!("Profile updated"),
-                    V1Response::Error { kind } => {
-                        error!("Recieved error {kind}")
-                    }
-                    _ => unreachable!()
+                match client.set_profile(profile.clone()) {
+                    Ok(()) => info!("Profile updated"),
+                    Err(e) => error!("{e}"),
                 }
             }
             ["help"] => println!("[Commands]\n1. write\n2. exit\n3. rm [index]: remove detail\n4. detail: add detail\n5. desc [replace]: replace description with string\n6. contact: add contact\n7. help\n"),
@@ -160,7 +121,7 @@ pub fn set_profile(
         }
     }
 
-    Ok("Ran".to_string())
+    Ok(Output::new("Ran"))
 }
 
 fn profile_error(profile: &ProfileCustomisable) -> Option<String> {
@@ -1,11 +1,23 @@
-use std::{collections::HashMap, env, error::Error, fs};
+use std::{env, error::Error, fs};
 
 use log::*;
 
-pub fn clean(_map: HashMap<String, String>, _args: Vec<String>) -> Result<String, Box<dyn Error>> {
+use crate::args::{Args, Spec};
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Remove all cached content",
+    args: &[],
+    login: false,
+    examples: &["clean"],
+    related: &["storage cat"],
+    run: clean,
+};
+
+pub fn clean(_map: Args) -> Result<Output, Box<dyn Error>> {
     let path = dirs::cache_dir().unwrap().join(env!("CARGO_PKG_NAME"));
     info!("Cleaning all items in {:?}", path);
-    fs::remove_dir_all(path)?;
+    fs::remove_dir_all(&path)?;
     info!("Directory cleared");
-    Ok(String::from("Deleted"))
+    Ok(Output::new("Deleted").field("path", path))
 }
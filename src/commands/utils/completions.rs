@@ -0,0 +1,29 @@
+use std::error::Error;
+
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::commands::commands;
+use crate::completions::script;
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Print a completion script for your shell",
+    args: &[Arg::pos("shell")
+        .kind(Kind::Choice(&["bash", "zsh", "fish"]))
+        .prompt("Shell (bash, zsh, fish)")],
+    login: false,
+    examples: &[
+        "completions bash > ~/.local/share/bash-completion/completions/gm-cli",
+        "completions zsh > ~/.zfunc/_gm-cli",
+        "completions fish > ~/.config/fish/completions/gm-cli.fish",
+    ],
+    related: &["help"],
+    run: completions,
+};
+
+pub fn completions(map: Args) -> Result<Output, Box<dyn Error>> {
+    let script = script(&commands(), map.str("shell"));
+    Ok(Output::new("You're welcome!")
+        .text(script.trim_end())
+        .field("shell", map.str("shell"))
+        .field("script", &script))
+}
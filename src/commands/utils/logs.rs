@@ -0,0 +1,109 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{stdout, Read, Seek, SeekFrom, Write},
+    thread,
+    time::Duration,
+};
+
+use serde_json::json;
+
+use crate::args::{Arg, Args, Kind, Spec};
+use crate::error::Error as CError;
+use crate::logs;
+use crate::output::{print_text, Format, Output};
+
+pub const SPEC: Spec = Spec {
+    about: "Show the log of a recent run",
+    args: &[
+        Arg::pos("run")
+            .kind(Kind::Uint)
+            .default("1")
+            .help("How many runs back, 1 is the run before this one"),
+        Arg::flag("list").help("List saved logs instead"),
+        Arg::opt("tail")
+            .kind(Kind::Uint)
+            .help("Only show this many lines from the end"),
+        Arg::flag("follow").help("Keep printing lines as the run writes them"),
+    ],
+    login: false,
+    examples: &["logs", "logs 2 --tail 20", "logs --list", "logs --follow"],
+    related: &["clean"],
+    run: logs,
+};
+
+pub fn logs(map: Args) -> Result<Output, Box<dyn Error>> {
+    let saved = logs::list().map_err(|e| CError::io(&logs::dir(), e))?;
+
+    if map.flag("list") {
+        let text = saved
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, path)| {
+                let size = path.metadata().map(|meta| meta.len()).unwrap_or_default();
+                format!("{:>3}  {}  {size} bytes", i + 1, path.display())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let items = saved
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, path)| json!({ "run": i + 1, "path": path }))
+            .collect::<Vec<_>>();
+        return Ok(Output::new("Listed").text(text).field("logs", items));
+    }
+
+    let run = map.uint("run")? as usize;
+    let path = match run
+        .checked_sub(1)
+        .and_then(|back| saved.iter().rev().nth(back))
+    {
+        Some(path) => path,
+        None => {
+            return Err(CError::Usage(format!(
+                "`run` must be between 1 and {}, got `{run}`",
+                saved.len()
+            ))
+            .into())
+        }
+    };
+
+    let content = fs::read_to_string(path).map_err(|e| CError::io(path, e))?;
+    let lines = content.lines().collect::<Vec<_>>();
+    let skip = match map.opt("tail") {
+        Some(_) => lines.len().saturating_sub(map.uint("tail")? as usize),
+        None => 0,
+    };
+    let text = lines[skip..].join("\n");
+
+    if !map.flag("follow") {
+        return Ok(Output::new("Shown")
+            .text(text.as_str())
+            .field("run", run)
+            .field("path", path)
+            .field("lines", &lines[skip..]));
+    }
+
+    if !Format::get().is_text() {
+        return Err(CError::Usage("`--follow` only works with text output".to_string()).into());
+    }
+
+    print_text(&text);
+    let mut file = File::open(path).map_err(|e| CError::io(path, e))?;
+    file.seek(SeekFrom::Start(content.len() as u64))
+        .map_err(|e| CError::io(path, e))?;
+    // runs until interrupted
+    loop {
+        let mut new = String::new();
+        file.read_to_string(&mut new)
+            .map_err(|e| CError::io(path, e))?;
+        if !new.is_empty() {
+            print!("{new}");
+            // lines may arrive in parts, which stay buffered until a newline otherwise
+            stdout().flush()?;
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
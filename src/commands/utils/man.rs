@@ -0,0 +1,21 @@
+use std::error::Error;
+
+use crate::args::{Args, Spec};
+use crate::help::manpage;
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Print a man page of every command",
+    args: &[],
+    login: false,
+    examples: &["man > gm-cli.1", "man | man -l -"],
+    related: &["help"],
+    run: man,
+};
+
+pub fn man(_map: Args) -> Result<Output, Box<dyn Error>> {
+    let manpage = manpage();
+    Ok(Output::new("You're welcome!")
+        .text(manpage.trim_end())
+        .field("manpage", &manpage))
+}
@@ -1,15 +1,21 @@
 use std::collections::HashMap;
 
-use crate::traits::types::CommandFnType;
+use crate::args::Spec;
 
 mod clean;
+mod completions;
 mod help;
+mod logs;
+mod man;
 mod version;
 
-pub fn commands() -> HashMap<&'static str, CommandFnType> {
+pub fn commands() -> HashMap<&'static str, &'static Spec> {
     HashMap::from([
-        ("clean", Box::new(clean::clean) as CommandFnType),
-        ("help", Box::new(help::help) as CommandFnType),
-        ("version", Box::new(version::version) as CommandFnType),
+        ("clean", &clean::SPEC),
+        ("completions", &completions::SPEC),
+        ("help", &help::SPEC),
+        ("logs", &logs::SPEC),
+        ("man", &man::SPEC),
+        ("version", &version::SPEC),
     ])
 }
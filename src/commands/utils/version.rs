@@ -1,9 +1,24 @@
-use std::{collections::HashMap, error::Error};
+use std::error::Error;
 
-pub fn version(
-    _map: HashMap<String, String>,
-    _args: Vec<String>,
-) -> Result<String, Box<dyn Error>> {
-    println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-    Ok("You're welcome!".to_string())
+use crate::args::{Args, Spec};
+use crate::output::Output;
+
+pub const SPEC: Spec = Spec {
+    about: "Print version info and exit",
+    args: &[],
+    login: false,
+    examples: &["version"],
+    related: &[],
+    run: version,
+};
+
+pub fn version(_map: Args) -> Result<Output, Box<dyn Error>> {
+    Ok(Output::new("You're welcome!")
+        .text(format!(
+            "{} {}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        ))
+        .field("name", env!("CARGO_PKG_NAME"))
+        .field("version", env!("CARGO_PKG_VERSION")))
 }
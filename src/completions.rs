@@ -0,0 +1,426 @@
+use std::fmt::Write;
+
+use crate::args::{Arg, Kind, Spec, GLOBAL};
+use crate::command::is_hidden;
+use crate::commands::aliases;
+use crate::Command;
+
+const BIN: &str = env!("CARGO_PKG_NAME");
+
+/// Completion script for `shell`, one of `bash`, `zsh` or `fish`.
+///
+/// Commands and flags are written into the script, paths in your storage are
+/// listed by the hidden `storage __complete-path` command when completing.
+pub fn script(root: &Command, shell: &str) -> String {
+    let tree = Tree::new(root);
+    match shell {
+        "bash" => bash(&tree),
+        "zsh" => zsh(&tree),
+        "fish" => fish(&tree),
+        _ => unreachable!(),
+    }
+}
+
+/// The command tree flattened into what completion scripts look up.
+struct Tree {
+    /// Path of every category, with the name and description of its subcommands.
+    categories: Vec<(String, Vec<(String, &'static str)>)>,
+    /// Path of every command, with its spec.
+    commands: Vec<(String, &'static Spec)>,
+}
+
+impl Tree {
+    fn new(root: &Command) -> Self {
+        let mut tree = Self {
+            categories: Vec::new(),
+            commands: Vec::new(),
+        };
+        tree.walk(root, Vec::new());
+
+        // aliases naming a whole command or namespace complete like it
+        let mut aliases = aliases().into_iter().collect::<Vec<_>>();
+        aliases.sort();
+        for (alias, target) in aliases {
+            let named = match root {
+                Command::Category(cog) => cog.contains_key(alias.as_str()),
+                Command::Command(_) => true,
+            };
+            if named || alias.contains(char::is_whitespace) {
+                continue;
+            }
+
+            match find(root, &target) {
+                Some(Command::Command(spec)) => {
+                    tree.categories[0].1.push((alias.clone(), spec.about));
+                    tree.commands.push((alias, spec));
+                }
+                Some(Command::Category(_)) => {
+                    let children = tree
+                        .categories
+                        .iter()
+                        .find(|(path, _)| *path == target)
+                        .map(|(_, children)| children.clone())
+                        .unwrap_or_default();
+                    tree.categories[0].1.push((alias.clone(), ""));
+                    tree.categories.push((alias, children));
+                }
+                None => {}
+            }
+        }
+        tree.categories[0].1.sort();
+        tree
+    }
+
+    fn walk(&mut self, command: &Command, path: Vec<&'static str>) {
+        let cog = match command {
+            Command::Command(spec) => {
+                self.commands.push((path.join(" "), spec));
+                return;
+            }
+            Command::Category(cog) => cog,
+        };
+
+        let mut children = cog
+            .iter()
+            .filter(|(name, _)| !is_hidden(name))
+            .collect::<Vec<_>>();
+        children.sort_by_key(|(name, _)| **name);
+
+        self.categories.push((
+            path.join(" "),
+            children
+                .iter()
+                .map(|(name, child)| match child {
+                    Command::Command(spec) => (name.to_string(), spec.about),
+                    Command::Category(_) => (name.to_string(), ""),
+                })
+                .collect(),
+        ));
+        for (name, child) in children {
+            self.walk(child, [path.as_slice(), &[*name]].concat());
+        }
+    }
+}
+
+/// The command or namespace at a path such as `storage ls`.
+fn find<'a>(root: &'a Command, path: &str) -> Option<&'a Command> {
+    path.split_whitespace()
+        .try_fold(root, |command, name| match command {
+            Command::Category(cog) => cog.get(name),
+            Command::Command(_) => None,
+        })
+}
+
+/// Options of a command that take a value, including global ones.
+fn options(spec: &Spec) -> impl Iterator<Item = &Arg> {
+    spec.args
+        .iter()
+        .chain(GLOBAL)
+        .filter(|arg| arg.kind.takes_value())
+}
+
+/// What a value is completed with, as understood by the scripts: `path`, `file` or `choice a b`.
+fn value(kind: Kind) -> Option<String> {
+    match kind {
+        Kind::Path => Some("path".to_string()),
+        Kind::File => Some("file".to_string()),
+        Kind::Choice(choices) => Some(format!("choice {}", choices.join(" "))),
+        _ => None,
+    }
+}
+
+/// Lookup functions shared by bash and zsh, `$1` is the command path.
+fn posix_functions(tree: &Tree) -> String {
+    let globals = GLOBAL
+        .iter()
+        .filter(|arg| arg.kind.takes_value())
+        .map(|arg| arg.name)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let global_flags = GLOBAL
+        .iter()
+        .map(|arg| format!("--{}", arg.name))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut out = String::from("_gm_cli_children() {\n    case \"$1\" in\n");
+    for (path, children) in &tree.categories {
+        let names = children
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(out, "        '{path}') echo '{names}' ;;");
+    }
+    out.push_str("    esac\n}\n\n_gm_cli_options() {\n    case \"$1\" in\n");
+    for (path, spec) in &tree.commands {
+        let names = options(spec)
+            .map(|arg| arg.name)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(out, "        '{path}') echo '{names}' ;;");
+    }
+    let _ = writeln!(out, "        *) echo '{globals}' ;;");
+    out.push_str("    esac\n}\n\n_gm_cli_flags() {\n    case \"$1\" in\n");
+    for (path, spec) in &tree.commands {
+        let flags = spec
+            .args
+            .iter()
+            .chain(GLOBAL)
+            .map(|arg| format!("--{}", arg.name))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(out, "        '{path}') echo '{flags}' ;;");
+    }
+    let _ = writeln!(out, "        *) echo '{global_flags}' ;;");
+
+    // `$2` is the index of a positional argument, or an option such as `--user`
+    out.push_str("    esac\n}\n\n_gm_cli_value() {\n    case \"$1:$2\" in\n");
+    for (path, spec) in &tree.commands {
+        for (i, arg) in spec.positional().enumerate() {
+            if let Some(value) = value(arg.kind) {
+                let _ = writeln!(out, "        '{path}:{i}') echo '{value}' ;;");
+            }
+        }
+        for arg in spec.args.iter().filter(|arg| !arg.positional) {
+            if let Some(value) = value(arg.kind) {
+                let _ = writeln!(out, "        '{path}:--{}') echo '{value}' ;;", arg.name);
+            }
+        }
+    }
+    for arg in GLOBAL {
+        if let Some(value) = value(arg.kind) {
+            let _ = writeln!(out, "        *:--{}) echo '{value}' ;;", arg.name);
+        }
+    }
+    out.push_str("    esac\n}\n");
+    out
+}
+
+fn bash(tree: &Tree) -> String {
+    format!(
+        r#"# bash completion for {BIN}, generated by `{BIN} completions bash`
+
+{}
+_gm_cli() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    local cmd="" pos=0 i word value
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        word="${{COMP_WORDS[i]}}"
+        if [[ "$word" == -* ]]; then
+            [[ "$word" != *=* && " $(_gm_cli_options "$cmd") " == *" ${{word#--}} "* ]] && ((i++))
+            continue
+        fi
+        if [[ " $(_gm_cli_children "$cmd") " == *" $word "* ]]; then
+            cmd="${{cmd:+$cmd }}$word"
+        else
+            ((pos++))
+        fi
+    done
+
+    if [[ "$prev" == --* && " $(_gm_cli_options "$cmd") " == *" ${{prev#--}} "* ]]; then
+        value="$(_gm_cli_value "$cmd" "$prev")"
+    elif [[ "$cur" == --* ]]; then
+        COMPREPLY=($(compgen -W "$(_gm_cli_flags "$cmd")" -- "$cur"))
+        return
+    elif [[ -n "$(_gm_cli_children "$cmd")" ]]; then
+        COMPREPLY=($(compgen -W "$(_gm_cli_children "$cmd")" -- "$cur"))
+        return
+    else
+        value="$(_gm_cli_value "$cmd" "$pos")"
+    fi
+
+    case "$value" in
+        path)
+            local IFS=$'\n'
+            COMPREPLY=($({BIN} storage __complete-path "$cur" 2>/dev/null </dev/null))
+            compopt -o nospace
+            ;;
+        file) COMPREPLY=($(compgen -f -- "$cur")) ;;
+        choice\ *) COMPREPLY=($(compgen -W "${{value#choice }}" -- "$cur")) ;;
+    esac
+}}
+
+complete -F _gm_cli {BIN}
+"#,
+        posix_functions(tree)
+    )
+}
+
+fn zsh(tree: &Tree) -> String {
+    format!(
+        r#"#compdef {BIN}
+# zsh completion for {BIN}, generated by `{BIN} completions zsh`
+
+{}
+_gm_cli() {{
+    local cur="${{words[CURRENT]}}" prev="${{words[CURRENT-1]}}"
+    local cmd="" pos=0 i word value
+    for ((i = 2; i < CURRENT; i++)); do
+        word="${{words[i]}}"
+        if [[ "$word" == -* ]]; then
+            [[ "$word" != *=* && " $(_gm_cli_options "$cmd") " == *" ${{word#--}} "* ]] && ((i++))
+            continue
+        fi
+        if [[ " $(_gm_cli_children "$cmd") " == *" $word "* ]]; then
+            cmd="${{cmd:+$cmd }}$word"
+        else
+            ((pos++))
+        fi
+    done
+
+    if [[ "$prev" == --* && " $(_gm_cli_options "$cmd") " == *" ${{prev#--}} "* ]]; then
+        value="$(_gm_cli_value "$cmd" "$prev")"
+    elif [[ "$cur" == --* ]]; then
+        compadd -- ${{=$(_gm_cli_flags "$cmd")}}
+        return
+    elif [[ -n "$(_gm_cli_children "$cmd")" ]]; then
+        compadd -- ${{=$(_gm_cli_children "$cmd")}}
+        return
+    else
+        value="$(_gm_cli_value "$cmd" "$pos")"
+    fi
+
+    case "$value" in
+        path) compadd -S '' -- ${{(f)"$({BIN} storage __complete-path "$cur" 2>/dev/null </dev/null)"}} ;;
+        file) _files ;;
+        choice\ *) compadd -- ${{=value#choice }} ;;
+    esac
+}}
+
+if [[ "${{funcstack[1]}}" == "_{BIN}" ]]; then
+    _gm_cli "$@"
+else
+    compdef _gm_cli {BIN}
+fi
+"#,
+        posix_functions(tree)
+    )
+}
+
+fn fish(tree: &Tree) -> String {
+    let mut out = format!(
+        r#"# fish completion for {BIN}, generated by `{BIN} completions fish`
+
+function __gm_cli_children
+    switch $argv[1]
+"#
+    );
+    for (path, children) in &tree.categories {
+        let names = children
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            out,
+            "        case '{path}'\n            string split ' ' -- '{names}'"
+        );
+    }
+    out.push_str("    end\nend\n\nfunction __gm_cli_options\n    switch $argv[1]\n");
+    for (path, spec) in &tree.commands {
+        let names = options(spec)
+            .map(|arg| arg.name)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            out,
+            "        case '{path}'\n            string split ' ' -- '{names}'"
+        );
+    }
+    out.push_str(
+        r#"    end
+end
+
+# prints the command path and how many positional arguments follow it
+function __gm_cli_state
+    set -l cmd ''
+    set -l pos 0
+    set -l skip 0
+    for word in (commandline -opc)[2..-1]
+        if test $skip = 1
+            set skip 0
+            continue
+        end
+        if string match -q -- '-*' $word
+            if not string match -q -- '*=*' $word; and contains -- (string sub -s 3 -- $word) (__gm_cli_options $cmd)
+                set skip 1
+            end
+            continue
+        end
+        if contains -- $word (__gm_cli_children $cmd)
+            set cmd (string trim -- "$cmd $word")
+        else
+            set pos (math $pos + 1)
+        end
+    end
+    echo $cmd
+    echo $pos
+end
+
+# true if the command path is `$argv[1]`, and the next positional argument is `$argv[2]` if given
+function __gm_cli_at
+    set -l state (__gm_cli_state)
+    test "$state[1]" = "$argv[1]"; or return 1
+    test (count $argv) -lt 2; or test "$state[2]" = "$argv[2]"
+end
+
+"#,
+    );
+
+    let _ = writeln!(out, "complete -c {BIN} -f");
+    for arg in GLOBAL {
+        let _ = writeln!(out, "complete -c {BIN} {}", fish_flag(arg));
+    }
+    for (path, children) in &tree.categories {
+        for (name, about) in children {
+            let _ = writeln!(
+                out,
+                "complete -c {BIN} -n \"__gm_cli_at '{path}'\" -a {name} -d {}",
+                fish_quote(about)
+            );
+        }
+    }
+    for (path, spec) in &tree.commands {
+        for arg in spec.args.iter().filter(|arg| !arg.positional) {
+            let _ = writeln!(
+                out,
+                "complete -c {BIN} -n \"__gm_cli_at '{path}'\" {}",
+                fish_flag(arg)
+            );
+        }
+        for (i, arg) in spec.positional().enumerate() {
+            let values = match arg.kind {
+                Kind::Path => format!(
+                    "-a \"({BIN} storage __complete-path (commandline -ct) 2>/dev/null </dev/null)\""
+                ),
+                Kind::File => "-F".to_string(),
+                Kind::Choice(choices) => format!("-a '{}'", choices.join(" ")),
+                _ => continue,
+            };
+            let _ = writeln!(
+                out,
+                "complete -c {BIN} -n \"__gm_cli_at '{path}' {i}\" {values}"
+            );
+        }
+    }
+    out
+}
+
+fn fish_flag(arg: &Arg) -> String {
+    let values = match arg.kind {
+        Kind::Switch | Kind::Count => String::new(),
+        Kind::Path => {
+            format!(" -x -a \"({BIN} storage __complete-path (commandline -ct) 2>/dev/null </dev/null)\"")
+        }
+        Kind::File => " -r -F".to_string(),
+        Kind::Choice(choices) => format!(" -x -a '{}'", choices.join(" ")),
+        _ => " -x".to_string(),
+    };
+    let short = arg.short.map(|c| format!(" -s {c}")).unwrap_or_default();
+    format!("-l {}{short}{values} -d {}", arg.name, fish_quote(arg.help))
+}
+
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
@@ -1,24 +1,151 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+};
 
+use log::*;
 use serde::{Deserialize, Serialize};
 
+use crate::functions::{seal, unlock, unseal};
 use crate::traits::ConfigTriat;
 
 #[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(from = "AccountConfigFile")]
 pub struct AccountConfig {
+    pub active: String,
+    pub accounts: BTreeMap<String, Account>,
+    /// Present when tokens are stored encrypted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<Encryption>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Account {
     pub id: i64,
     pub instance: String,
+    #[serde(default)]
     pub token: String,
+    #[serde(default)]
+    pub username: String,
+    /// Encrypted token, only used on disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Encryption {
+    /// Base64 salt for deriving the key from the passphrase.
+    pub salt: String,
+}
+
+/// Older versions stored a single login at the top level of `account.yml`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountConfigFile {
+    Legacy {
+        id: i64,
+        instance: String,
+        token: String,
+    },
+    Current {
+        #[serde(default)]
+        active: String,
+        #[serde(default)]
+        accounts: BTreeMap<String, Account>,
+        #[serde(default)]
+        encryption: Option<Encryption>,
+    },
+}
+
+impl From<AccountConfigFile> for AccountConfig {
+    fn from(value: AccountConfigFile) -> Self {
+        match value {
+            AccountConfigFile::Current {
+                active,
+                accounts,
+                encryption,
+            } => Self {
+                active,
+                accounts,
+                encryption,
+            },
+            AccountConfigFile::Legacy {
+                id,
+                instance,
+                token,
+            } => {
+                let account = Account {
+                    id,
+                    instance,
+                    token,
+                    username: String::new(),
+                    sealed: None,
+                };
+
+                let mut config = Self::default();
+                if account.is_loggedin() {
+                    config.insert("default".to_string(), account);
+                }
+                config
+            }
+        }
+    }
 }
 
 impl ConfigTriat for AccountConfig {
     const NAME: &'static str = "account";
+
+    fn load() -> Result<Self, Box<dyn Error>> {
+        let mut config = Self::read()?;
+
+        if let Some(encryption) = &config.encryption {
+            let key = unlock(&encryption.salt)?;
+            for (name, account) in config.accounts.iter_mut() {
+                if let Some(sealed) = account.sealed.take() {
+                    debug!("Decrypting token for `{name}`");
+                    account.token = unseal(&sealed, key)?;
+                }
+            }
+        }
+
+        debug!("Saving config file after load to {:?}", Self::path());
+        config.save()?;
+        Ok(config)
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        let encryption = match &self.encryption {
+            Some(encryption) => encryption,
+            None => return self.write(),
+        };
+
+        let key = unlock(&encryption.salt)?;
+        let mut sealed = self.clone();
+        for account in sealed.accounts.values_mut() {
+            if !account.token.is_empty() {
+                account.sealed = Some(seal(&account.token, key)?);
+                account.token.clear();
+            }
+        }
+
+        sealed.write()
+    }
+
+    fn to_map(&self) -> HashMap<String, String> {
+        self.account_map(&self.active)
+    }
 }
 
-impl AccountConfig {
+impl Account {
     pub fn is_loggedin(&self) -> bool {
         !(self.id == 0 || self.instance.is_empty() || self.token.is_empty())
     }
+}
+
+impl AccountConfig {
+    pub fn is_loggedin(&self) -> bool {
+        self.active().is_some_and(Account::is_loggedin)
+    }
 
     pub fn is_loggedin_map(map: &HashMap<String, String>) -> bool {
         map.contains_key("id")
@@ -28,4 +155,87 @@ impl AccountConfig {
             && map.contains_key("token")
             && !map.get("token").unwrap().is_empty()
     }
+
+    pub fn active(&self) -> Option<&Account> {
+        self.accounts.get(&self.active)
+    }
+
+    /// Saves an account under `name` and makes it the active one, returns the replaced account.
+    pub fn insert(&mut self, name: String, account: Account) -> Option<Account> {
+        self.active = name.clone();
+        self.accounts.insert(name, account)
+    }
+
+    /// Removes an account, switching to the first remaining one if it was active.
+    pub fn remove(&mut self, name: &str) -> Option<Account> {
+        let account = self.accounts.remove(name)?;
+
+        if self.active == name {
+            self.active = self.accounts.keys().next().cloned().unwrap_or_default();
+        }
+
+        Some(account)
+    }
+
+    pub fn account_map(&self, name: &str) -> HashMap<String, String> {
+        match self.accounts.get(name) {
+            Some(account) => HashMap::from([
+                ("account".to_string(), name.to_string()),
+                ("id".to_string(), account.id.to_string()),
+                ("instance".to_string(), account.instance.clone()),
+                ("token".to_string(), account.token.clone()),
+            ]),
+            None => HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_login_becomes_default_account() {
+        let config: AccountConfig =
+            serde_yaml::from_str("id: 7\ninstance: gmornin.mnpn.dev\ntoken: abc").unwrap();
+
+        assert_eq!(config.active, "default");
+        let account = config.active().unwrap();
+        assert_eq!(account.id, 7);
+        assert_eq!(account.instance, "gmornin.mnpn.dev");
+        assert_eq!(account.token, "abc");
+        assert!(config.encryption.is_none());
+    }
+
+    #[test]
+    fn legacy_logged_out_becomes_empty() {
+        let config: AccountConfig = serde_yaml::from_str("id: 0\ninstance: ''\ntoken: ''").unwrap();
+
+        assert!(config.accounts.is_empty());
+        assert!(!config.is_loggedin());
+    }
+
+    #[test]
+    fn migrated_config_reads_back_as_current() {
+        let config: AccountConfig =
+            serde_yaml::from_str("id: 7\ninstance: gmornin.mnpn.dev\ntoken: abc").unwrap();
+        let written = serde_yaml::to_string(&config).unwrap();
+        let read: AccountConfig = serde_yaml::from_str(&written).unwrap();
+
+        assert!(!written.contains("\nid: 7"));
+        assert_eq!(read.active, "default");
+        assert_eq!(read.active().unwrap().token, "abc");
+    }
+
+    #[test]
+    fn sealed_tokens_are_not_logged_in_until_unlocked() {
+        // what path completion sees when the passphrase can't be asked for
+        let config: AccountConfig = serde_yaml::from_str(
+            "active: a\naccounts:\n  a:\n    id: 7\n    instance: gm.example.com\n    sealed: abc\nencryption:\n  salt: c2FsdA==",
+        )
+        .unwrap();
+
+        assert!(!config.is_loggedin());
+        assert!(!AccountConfig::is_loggedin_map(&config.to_map()));
+    }
 }
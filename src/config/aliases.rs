@@ -0,0 +1,45 @@
+use std::{collections::HashMap, error::Error, sync::OnceLock};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::traits::ConfigTriat;
+
+static ALIASES: OnceLock<AliasesConfig> = OnceLock::new();
+
+/// Your own names for commands, such as `pubs: tex publishes alice:gm.example.com`.
+///
+/// The alias is replaced with the words it stands for, arguments after it are kept.
+/// Command names always win over aliases.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AliasesConfig(HashMap<String, String>);
+
+impl ConfigTriat for AliasesConfig {
+    const NAME: &'static str = "aliases";
+
+    fn load() -> Result<Self, Box<dyn Error>> {
+        let config = Self::read()?;
+
+        debug!("Saving config file after load to {:?}", Self::path());
+        config.save()?;
+        let _ = ALIASES.set(config.clone());
+        Ok(config)
+    }
+}
+
+impl AliasesConfig {
+    /// Aliases from the loaded config, empty if it was not loaded.
+    pub fn loaded() -> HashMap<String, String> {
+        ALIASES
+            .get()
+            .map(|config| {
+                config
+                    .0
+                    .iter()
+                    .filter(|(_, target)| !target.trim().is_empty())
+                    .map(|(alias, target)| (alias.clone(), target.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
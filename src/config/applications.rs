@@ -1,141 +1,269 @@
-use std::{
-    collections::HashMap,
-    error::Error,
-    ffi::OsStr,
-    fs,
-    io::{self, Write},
-    path::Path,
-    process::Stdio,
-};
+use std::{collections::HashMap, env, error::Error, io, path::Path, process::Stdio, thread};
 
 use log::*;
 use serde::{Deserialize, Serialize};
 use singlyton::SingletonUninit;
 
+use crate::error::Error as CError;
+use crate::functions::{interactive, prompt_cmd};
+use crate::output::Format;
 use crate::traits::ConfigTriat;
 
 pub static APPLICATIONS: SingletonUninit<ApplicationsConfig> = SingletonUninit::uninit();
 
+/// Rules for opening downloaded files, the first matching rule wins.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(from = "ApplicationsConfigFile")]
+pub struct ApplicationsConfig {
+    pub rules: Vec<Rule>,
+    /// Use `$EDITOR` for text and `xdg-open` for anything else when no rule matches,
+    /// instead of asking for a command.
+    pub fallback: bool,
+}
+
+/// Every condition that is set must match.
 #[derive(Serialize, Deserialize, Clone, Default)]
-pub struct ApplicationsConfig(Vec<HashMap<String, String>>);
+pub struct Rule {
+    /// Matched against the file name, or the whole path if it contains `/`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glob: Option<String>,
+    /// Extension without the dot, such as `pdf`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ext: Option<String>,
+    /// MIME type guessed from the extension, such as `image/png` or `text/*`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime: Option<String>,
+    /// Command to run, `{path}` is replaced with the file path.
+    pub command: String,
+    /// Run the command in the background without waiting for it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub detach: bool,
+}
 
-impl ConfigTriat for ApplicationsConfig {
-    const NAME: &'static str = "applications";
+/// Older versions stored a list of single entry maps from extension to command.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApplicationsConfigFile {
+    Legacy(Vec<HashMap<String, String>>),
+    Current {
+        #[serde(default)]
+        rules: Vec<Rule>,
+        #[serde(default = "fallback_default")]
+        fallback: bool,
+    },
+}
 
-    fn load() -> Result<Self, Box<dyn Error>> {
-        let path = Self::path();
-
-        debug!("Reading config file at {:?}", Self::path());
-
-        let mut config = if path.exists() {
-            let s = match fs::read_to_string(&path) {
-                Ok(s) => s,
-                Err(e) => {
-                    return Err(crate::error::Error::StringErr(format!(
-                        "unable to read config file at `{path:?}`: {e}",
-                    ))
-                    .into())
-                }
-            };
+fn fallback_default() -> bool {
+    true
+}
+
+impl Default for ApplicationsConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            fallback: fallback_default(),
+        }
+    }
+}
+
+impl From<ApplicationsConfigFile> for ApplicationsConfig {
+    fn from(value: ApplicationsConfigFile) -> Self {
+        match value {
+            ApplicationsConfigFile::Current { rules, fallback } => Self { rules, fallback },
+            ApplicationsConfigFile::Legacy(entries) if entries.is_empty() => Self::default(),
+            ApplicationsConfigFile::Legacy(entries) => {
+                let mut entries = entries
+                    .into_iter()
+                    .flat_map(|map| map.into_iter())
+                    .collect::<Vec<_>>();
+
+                // more specific extensions such as `tar.gz` were tried first
+                entries.sort_by(|(this, _), (other, _)| {
+                    let this_complexity = this.matches('.').count();
+                    let other_complexity = other.matches('.').count();
+
+                    other_complexity
+                        .cmp(&this_complexity)
+                        .then(other.len().cmp(&this.len()))
+                });
 
-            match serde_yaml::from_str(&s) {
-                Ok(v) => v,
-                Err(e) => {
-                    return Err(crate::error::Error::StringErr(format!(
-                        "error parsing config file at `{path:?}`: {e}"
-                    ))
-                    .into())
+                Self {
+                    rules: entries
+                        .into_iter()
+                        .map(|(ext, command)| Rule {
+                            ext: Some(ext),
+                            command,
+                            ..Default::default()
+                        })
+                        .collect(),
+                    // old files relied on being asked
+                    fallback: false,
                 }
             }
-        } else {
-            info!("No config file found at {:?}, using default", Self::path());
-            Self::default()
-        };
+        }
+    }
+}
+
+impl ConfigTriat for ApplicationsConfig {
+    const NAME: &'static str = "applications";
+
+    fn load() -> Result<Self, Box<dyn Error>> {
+        let config = Self::read()?;
 
         debug!("Saving config file after load to {:?}", Self::path());
 
-        config.sort();
         config.save()?;
         APPLICATIONS.init(config.clone());
         Ok(config)
     }
 }
 
-impl ApplicationsConfig {
-    pub fn sort(&mut self) {
-        self.0.sort_by(|this, other| {
-            let this_str = this.keys().last().unwrap();
-            let other_str = other.keys().last().unwrap();
-
-            if this_str == other_str {
-                warn!("There seemed to be a double entry on `{this_str}`");
-                warn!(
-                    "Chech your configuration at {:?} to remove it",
-                    Self::path()
-                );
+impl Rule {
+    pub fn matches(&self, path: &Path) -> bool {
+        let path_str = path.to_str().unwrap_or_default();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+
+        if let Some(pattern) = &self.glob {
+            let target = if pattern.contains('/') {
+                path_str
+            } else {
+                name
+            };
+            if !glob_match(pattern.as_bytes(), target.as_bytes()) {
+                return false;
             }
+        }
 
-            let this_complexity = this_str.as_bytes().iter().filter(|b| b == &&b'.').count();
-            let other_complexity = other_str.as_bytes().iter().filter(|b| b == &&b'.').count();
+        // compared against the end of the name so `tar.gz` works
+        if let Some(ext) = &self.ext {
+            if !name.ends_with(&format!(".{}", ext.trim_start_matches('.'))) {
+                return false;
+            }
+        }
 
-            if this_complexity != other_complexity {
-                return other_complexity.cmp(&this_complexity);
+        if let Some(mime) = &self.mime {
+            let guessed = mime_guess::from_path(path).first_or_octet_stream();
+            let matched = match mime.split_once('/') {
+                Some((kind, "*")) => guessed.type_() == kind,
+                _ => guessed.essence_str() == mime,
+            };
+            if !matched {
+                return false;
             }
+        }
+
+        true
+    }
+
+    /// Short description of the conditions, such as `ext=pdf mime=application/pdf`.
+    pub fn conditions(&self) -> String {
+        let conditions = [
+            ("glob", &self.glob),
+            ("ext", &self.ext),
+            ("mime", &self.mime),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some(format!("{key}={}", value.as_ref()?)))
+        .collect::<Vec<_>>();
+
+        if conditions.is_empty() {
+            "*".to_string()
+        } else {
+            conditions.join(" ")
+        }
+    }
+}
 
-            other_str.len().cmp(&this_str.len())
-        })
+impl ApplicationsConfig {
+    /// Index of the first rule matching `path`.
+    pub fn find(&self, path: &Path) -> Option<usize> {
+        self.rules.iter().position(|rule| rule.matches(path))
+    }
+
+    /// Command used when no rule matches.
+    pub fn fallback_command(path: &Path) -> String {
+        let is_text = mime_guess::from_path(path)
+            .first()
+            .is_some_and(|mime| mime.type_() == "text");
+
+        match env::var("EDITOR") {
+            Ok(editor) if is_text && !editor.is_empty() => format!("{editor} {{path}}"),
+            _ if cfg!(target_os = "macos") => "open {path}".to_string(),
+            _ if cfg!(windows) => "start \"\" {path}".to_string(),
+            _ => "xdg-open {path}".to_string(),
+        }
     }
 
-    pub fn get(&self, k: &str) -> Option<&str> {
-        Some(
-            self.0
-                .iter()
-                .find(|map| map.keys().last().unwrap() == k)?
-                .values()
-                .last()
-                .unwrap(),
-        )
+    /// The command template for `path`, and the matching rule if there is one.
+    pub fn command(&self, path: &Path) -> (String, Option<usize>) {
+        match self.find(path) {
+            Some(i) => (self.rules[i].command.clone(), Some(i)),
+            None => (Self::fallback_command(path), None),
+        }
     }
 
-    pub fn prompt_get(&mut self, k: &str) -> Result<String, Box<dyn Error>> {
-        if let Some(c) = self.get(k) {
-            return Ok(c.to_string());
+    pub fn prompt_get(&mut self, path: &Path) -> Result<String, Box<dyn Error>> {
+        if self.fallback || self.find(path).is_some() {
+            return Ok(self.command(path).0);
         }
 
-        let mut stdout = io::stdout();
-        let stdin = io::stdin();
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
 
-        println!("What command would you like to open files with a `{k}` extension?");
-        println!(
+        if !interactive() {
+            return Err(CError::Usage(format!(
+                "no command opens `{ext}` files, add one with `apps add` when input is disabled"
+            ))
+            .into());
+        }
+
+        eprintln!("What command would you like to open files with a `{ext}` extension?");
+        eprintln!(
             "Type the full command, use `{{path}}` as a placeholder, such as `firefox {{path}}`"
         );
-        print!("> ");
-        stdout.flush().unwrap();
-
-        let mut buf = String::new();
-        stdin.read_line(&mut buf).unwrap();
-        let cmd = buf.trim();
-        self.0
-            .push(HashMap::from([(k.to_string(), cmd.to_string())]));
+        let cmd = prompt_cmd()?.join(" ");
+        self.rules.push(Rule {
+            ext: Some(ext.to_string()),
+            command: cmd.clone(),
+            ..Default::default()
+        });
         self.save()?;
-        Ok(cmd.to_string())
+        Ok(cmd)
     }
 
-    pub fn open(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+    /// Opens `path`, waiting for the command to finish unless `detach` or the matching rule says otherwise.
+    pub fn open(&mut self, path: &Path, detach: bool) -> Result<(), Box<dyn Error>> {
         let cmd = self
-            .prompt_get(path.extension().unwrap_or(OsStr::new("")).to_str().unwrap())?
+            .prompt_get(path)?
             .replace("{path}", path.to_str().unwrap());
+        let detach = detach || self.find(path).is_some_and(|i| self.rules[i].detach);
+
+        if detach {
+            info!("Opening file in the background with command `{cmd}`");
+            return spawn_detached(&cmd);
+        }
+
         info!("Opening file with command `{cmd}`");
         let mut cmd = execute::command(cmd)
-            .stdout(Stdio::inherit())
+            // stdout is kept for the json or yaml document
+            .stdout(if Format::get().is_text() {
+                Stdio::inherit()
+            } else {
+                Stdio::from(io::stderr())
+            })
             .stderr(Stdio::inherit())
             .stdin(Stdio::inherit())
             .spawn()?;
 
         let status = cmd.wait()?;
         if !status.success() {
-            println!(
-                "Command failed with exit code {}.",
+            warn!(
+                "Command failed with exit code {}",
                 status.code().unwrap_or(-1)
             );
         }
@@ -148,3 +276,111 @@ impl ApplicationsConfig {
         Ok(())
     }
 }
+
+/// Starts `cmd` in its own process group with no terminal attached, so it outlives the CLI
+/// and isn't stopped by Ctrl + C in the `fs` repl.
+fn spawn_detached(cmd: &str) -> Result<(), Box<dyn Error>> {
+    let mut cmd = execute::command(cmd);
+    cmd.stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .stdin(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x00000008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+        cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    let mut child = cmd.spawn()?;
+
+    // reaps the child once it exits, so long running repls don't collect zombies
+    thread::spawn(move || {
+        if let Ok(status) = child.wait() {
+            if !status.success() {
+                debug!(
+                    "Detached command exited with code {}",
+                    status.code().unwrap_or(-1)
+                );
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Wildcard match supporting `*` and `?`.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == b'?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str, text: &str) -> bool {
+        glob_match(pattern.as_bytes(), text.as_bytes())
+    }
+
+    #[test]
+    fn star_matches_any_run() {
+        assert!(glob("*.pdf", "paper.pdf"));
+        assert!(glob("*.pdf", ".pdf"));
+        assert!(glob("a*b*c", "aXXbYYc"));
+        assert!(glob("a*b*c", "abbbc"));
+        assert!(glob("**", ""));
+        assert!(!glob("*.pdf", "paper.pdf.bak"));
+    }
+
+    #[test]
+    fn question_mark_matches_one() {
+        assert!(glob("?.md", "a.md"));
+        assert!(!glob("?.md", ".md"));
+        assert!(!glob("?.md", "ab.md"));
+    }
+
+    #[test]
+    fn anchored_at_both_ends() {
+        assert!(glob("notes", "notes"));
+        assert!(!glob("notes", "my-notes"));
+        assert!(!glob("notes", "notes.txt"));
+        assert!(glob("*notes*", "my-notes.txt"));
+    }
+
+    #[test]
+    fn empty_pattern_matches_empty_text() {
+        assert!(glob("", ""));
+        assert!(!glob("", "a"));
+        assert!(!glob("a", ""));
+    }
+}
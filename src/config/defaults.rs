@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use log::*;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::commands::canonical;
+use crate::traits::ConfigTriat;
+
+/// Default flag values, merged below account, environment and command line values.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct DefaultsConfig {
+    #[serde(default)]
+    pub global: HashMap<String, Value>,
+    /// Keyed by instance, such as `localhost:8080`.
+    #[serde(default)]
+    pub instances: HashMap<String, HashMap<String, Value>>,
+    /// Keyed by command, such as `tex publishes` or `account ls`, aliases work too.
+    #[serde(default)]
+    pub commands: HashMap<String, HashMap<String, Value>>,
+}
+
+impl ConfigTriat for DefaultsConfig {
+    const NAME: &'static str = "defaults";
+}
+
+impl DefaultsConfig {
+    /// Defaults for a command run against an instance, more specific entries win:
+    /// global, then instance, then command.
+    pub fn resolve(&self, instance: Option<&str>, command: &[&str]) -> HashMap<String, String> {
+        let mut merged = self.global.clone();
+
+        if let Some(values) = instance.and_then(|instance| self.instances.get(instance)) {
+            merged.extend(values.clone());
+        }
+
+        // keys may be aliases or names from before namespaces, such as `publishes`
+        let commands = self
+            .commands
+            .iter()
+            .map(|(key, values)| (canonical(key), values))
+            .collect::<HashMap<_, _>>();
+        for len in 1..=command.len() {
+            if let Some(values) = commands.get(&command[..len].join(" ")) {
+                merged.extend((*values).clone());
+            }
+        }
+
+        merged
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let value = match value {
+                    Value::Bool(true) => String::new(),
+                    Value::Bool(false) | Value::Null => return None,
+                    Value::Number(n) => n.to_string(),
+                    Value::String(s) => s,
+                    _ => {
+                        warn!("Ignoring default for `{key}`, only plain values are allowed");
+                        return None;
+                    }
+                };
+                Some((key, value))
+            })
+            .collect()
+    }
+}
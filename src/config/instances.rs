@@ -0,0 +1,111 @@
+use std::{collections::HashMap, error::Error, sync::OnceLock, time::Duration};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::traits::ConfigTriat;
+
+static INSTANCES: OnceLock<InstancesConfig> = OnceLock::new();
+
+/// Connection settings keyed by instance, such as `localhost:8080`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct InstancesConfig(HashMap<String, InstanceSettings>);
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct InstanceSettings {
+    /// `http` or `https`, overrides the `http` flag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    /// Host to connect to, the instance name is used if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Prefix put before `/api`, such as `/gm` when running behind a reverse proxy.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub base_path: String,
+    /// Request timeout in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Appended to the `gm-cli <version>` user agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+/// Where and how to send a request.
+pub struct Endpoint {
+    pub url: String,
+    pub timeout: Option<Duration>,
+    pub user_agent: String,
+}
+
+impl ConfigTriat for InstancesConfig {
+    const NAME: &'static str = "instances";
+
+    fn load() -> Result<Self, Box<dyn Error>> {
+        let config = Self::read()?;
+
+        debug!("Saving config file after load to {:?}", Self::path());
+        config.save()?;
+        let _ = INSTANCES.set(config.clone());
+        Ok(config)
+    }
+}
+
+impl InstancesConfig {
+    pub fn get(&self, instance: &str) -> Option<&InstanceSettings> {
+        self.0.get(instance)
+    }
+
+    /// Resolves a `{instance}/api/...` path against the loaded registry.
+    pub fn endpoint(path: &str, http: bool) -> Endpoint {
+        let (instance, rest) = path.split_once('/').unwrap_or((path, ""));
+        let settings = INSTANCES
+            .get()
+            .and_then(|config| config.get(instance))
+            .cloned()
+            .unwrap_or_default();
+        settings.endpoint(instance, rest, http)
+    }
+}
+
+impl InstanceSettings {
+    pub fn endpoint(&self, instance: &str, path: &str, http: bool) -> Endpoint {
+        let scheme = match &self.scheme {
+            Some(scheme) => scheme.as_str(),
+            None if http => "http",
+            None => "https",
+        };
+        let host = self.host.as_deref().unwrap_or(instance);
+        let port = self.port.map(|port| format!(":{port}")).unwrap_or_default();
+
+        let mut url = format!("{scheme}://{host}{port}");
+        for segment in [
+            self.base_path.trim_matches('/'),
+            path.trim_start_matches('/'),
+        ] {
+            if !segment.is_empty() {
+                url.push('/');
+                url.push_str(segment);
+            }
+        }
+
+        let mut user_agent = format!("gm-cli {}", env!("CARGO_PKG_VERSION"));
+        if let Some(suffix) = &self.user_agent {
+            user_agent.push(' ');
+            user_agent.push_str(suffix);
+        }
+
+        Endpoint {
+            url,
+            timeout: self.timeout.map(Duration::from_secs),
+            user_agent,
+        }
+    }
+}
+
+impl Endpoint {
+    pub fn is_http(&self) -> bool {
+        self.url.starts_with("http://")
+    }
+}
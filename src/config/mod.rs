@@ -1,5 +1,13 @@
 mod account;
+mod aliases;
 mod applications;
+mod defaults;
+mod instances;
+mod sources;
 
 pub use account::*;
+pub use aliases::*;
 pub use applications::*;
+pub use defaults::*;
+pub use instances::*;
+pub use sources::*;
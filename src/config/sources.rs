@@ -0,0 +1,91 @@
+use std::{collections::HashMap, env, fmt::Display, path::PathBuf};
+
+use singlyton::SingletonUninit;
+
+/// Where each effective value in the args map came from, filled in by `config_init`.
+pub static SOURCES: SingletonUninit<HashMap<String, Source>> = SingletonUninit::uninit();
+
+/// Environment variables and the args map keys they override.
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("GM_INSTANCE", "instance"),
+    ("GM_TOKEN", "token"),
+    ("GM_ID", "id"),
+    ("GM_HTTP", "http"),
+];
+
+/// Directory to read config files from instead of the platform default.
+pub const CONFIG_DIR_ENV: &str = "GM_CONFIG_DIR";
+
+#[derive(Clone)]
+pub enum Source {
+    File(PathBuf),
+    Env(&'static str),
+    Flag,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "config file {path:?}"),
+            Self::Env(var) => write!(f, "environment variable `{var}`"),
+            Self::Flag => f.write_str("command line flag"),
+        }
+    }
+}
+
+/// Applies `ENV_OVERRIDES` on top of `map`, returning the keys that were overridden.
+pub fn apply_env(map: &mut HashMap<String, String>) -> Vec<(String, Source)> {
+    let mut applied = Vec::new();
+
+    for (var, key) in ENV_OVERRIDES {
+        let value = match env::var(var) {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+
+        if *key == "http" {
+            if matches!(value.as_str(), "" | "0" | "false") {
+                map.remove(*key);
+            } else {
+                map.insert(key.to_string(), String::new());
+            }
+        } else {
+            map.insert(key.to_string(), value);
+        }
+
+        applied.push((key.to_string(), Source::Env(var)));
+    }
+
+    // the saved account name no longer matches a token or ID from the environment
+    if applied.iter().any(|(key, _)| key == "token" || key == "id") {
+        map.remove("account");
+    }
+
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_identity_drops_the_saved_account_name() {
+        let mut map = HashMap::from([
+            ("account".to_string(), "work".to_string()),
+            ("token".to_string(), "saved".to_string()),
+            ("http".to_string(), String::new()),
+        ]);
+
+        env::set_var("GM_TOKEN", "from-env");
+        env::set_var("GM_HTTP", "false");
+        let applied = apply_env(&mut map);
+        env::remove_var("GM_TOKEN");
+        env::remove_var("GM_HTTP");
+
+        assert_eq!(map.get("token").unwrap(), "from-env");
+        assert!(!map.contains_key("account"));
+        assert!(!map.contains_key("http"));
+        assert!(applied.iter().any(|(key, _)| key == "token"));
+        assert!(applied.iter().any(|(key, _)| key == "http"));
+    }
+}
@@ -1,20 +1,389 @@
-use std::fmt::Display;
+use std::{
+    error::Error as StdError,
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
+
+use goodmorning_bindings::services::v1::V1Error;
+
+use crate::Unknown;
+
+// Exit codes follow `sysexits.h` where one fits, anything else exits with `EXIT_FAILURE`.
+pub const EXIT_FAILURE: i32 = 1;
+/// The command line doesn't match the command, such as an unknown flag or a missing argument.
+pub const EXIT_USAGE: i32 = 64;
+/// The server could not compile the file.
+pub const EXIT_COMPILE: i32 = 65;
+/// The command needs an account and none is saved or given.
+pub const EXIT_NOT_LOGGED_IN: i32 = 67;
+/// The server could not be reached.
+pub const EXIT_NETWORK: i32 = 69;
+/// Reading or writing a local file failed.
+pub const EXIT_IO: i32 = 74;
+/// The server answered with an error, or with something that isn't a valid response.
+pub const EXIT_SERVER: i32 = 76;
+/// Exit code when the server rejects the saved token and there is no terminal to log in again.
+pub const EXIT_INVALID_TOKEN: i32 = 77;
+/// A config file could not be loaded, or its connection settings are invalid.
+pub const EXIT_CONFIG: i32 = 78;
 
 #[derive(Debug)]
 pub enum Error {
-    StringErr(String),
-    StrErr(&'static str),
-    Unspecified,
+    /// The command line doesn't match the command's spec, or one of its values is invalid.
+    Usage(String),
+    NotLoggedIn,
+    /// The server answered a request to `endpoint` with an error.
+    Server {
+        endpoint: String,
+        kind: V1Error,
+    },
+    /// A request to `endpoint` could not be sent, or its response could not be read.
+    Network {
+        endpoint: String,
+        source: reqwest::Error,
+    },
+    /// The server answered a request to `endpoint` with something that isn't a valid response.
+    Response {
+        endpoint: String,
+        source: serde_json::Error,
+    },
+    /// Reading or writing a local file failed.
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A config file could not be read as its config.
+    Config {
+        path: PathBuf,
+        message: String,
+    },
+    /// An uploaded file still read back with a different SHA-256 after every retry.
+    Checksum {
+        path: String,
+        expected: String,
+        got: String,
+    },
+    /// Anything else, such as a refused confirmation.
+    Other(String),
+}
+
+impl Error {
+    pub fn server(url: &str, kind: V1Error) -> Self {
+        Self::Server {
+            endpoint: endpoint(url),
+            kind,
+        }
+    }
+
+    pub fn network(url: &str, source: reqwest::Error) -> Self {
+        Self::Network {
+            endpoint: endpoint(url),
+            // the url may carry the token
+            source: source.without_url(),
+        }
+    }
+
+    /// The server answered a request to `url` with a response meant for another request.
+    pub fn unexpected(url: &str) -> Self {
+        Self::Other(format!("unexpected response from `{}`", endpoint(url)))
+    }
+
+    pub fn response(url: &str, source: serde_json::Error) -> Self {
+        Self::Response {
+            endpoint: endpoint(url),
+            source,
+        }
+    }
+
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn config(path: PathBuf, message: impl Display) -> Self {
+        Self::Config {
+            path,
+            message: message.to_string(),
+        }
+    }
+
+    /// Stable name of the variant, used in `--output json|yaml` errors.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Usage(_) => "usage",
+            Self::NotLoggedIn => "not_logged_in",
+            Self::Server { .. } => "server",
+            Self::Network { .. } => "network",
+            Self::Response { .. } => "response",
+            Self::Io { .. } => "io",
+            Self::Config { .. } => "config",
+            Self::Checksum { .. } => "checksum",
+            Self::Other(_) => "other",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Usage(_) => EXIT_USAGE,
+            Self::NotLoggedIn => EXIT_NOT_LOGGED_IN,
+            Self::Server { kind, .. } => v1_exit_code(kind),
+            Self::Network { .. } => EXIT_NETWORK,
+            Self::Response { .. } | Self::Checksum { .. } => EXIT_SERVER,
+            Self::Io { .. } => EXIT_IO,
+            Self::Config { .. } => EXIT_CONFIG,
+            Self::Other(_) => EXIT_FAILURE,
+        }
+    }
+
+    /// What the user could do about the error, if there is anything.
+    pub fn remedy(&self) -> Option<String> {
+        match self {
+            Self::Usage(_) => Some("Add `--help` to the command to see what it takes".to_string()),
+            Self::NotLoggedIn => Some(
+                "Run `gm-cli account login` or `gm-cli account create`, or pass `--instance`, `--id` and `--token`"
+                    .to_string(),
+            ),
+            Self::Server {
+                kind: V1Error::InvalidToken,
+                ..
+            } => Some(
+                "The token may have been regenerated elsewhere, run `gm-cli account login` again"
+                    .to_string(),
+            ),
+            Self::Server {
+                kind: V1Error::FileNotFound,
+                ..
+            } => Some("Check the path with `gm-cli storage ls`".to_string()),
+            Self::Server { .. } | Self::Other(_) => None,
+            Self::Network { .. } => Some(
+                "Check the instance address and your connection, add `--http` if the instance doesn't use https"
+                    .to_string(),
+            ),
+            Self::Response { .. } => Some(
+                "Check that the instance address points to a goodmorning instance of a compatible version"
+                    .to_string(),
+            ),
+            Self::Io { .. } => {
+                Some("Check that the file exists and that you are allowed to access it".to_string())
+            }
+            Self::Checksum { .. } => {
+                Some("Upload again, or allow more attempts with `--retries`".to_string())
+            }
+            Self::Config { path, .. } => Some(format!(
+                "Run `gm-cli config edit {}`, or delete the file to regenerate it",
+                path.file_stem().unwrap_or_default().to_string_lossy()
+            )),
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self {
-            Self::StringErr(e) => e,
-            Self::StrErr(e) => e,
-            Self::Unspecified => "unspecified error",
-        })
+        match self {
+            Self::Usage(e) | Self::Other(e) => f.write_str(e),
+            Self::NotLoggedIn => f.write_str("not logged in"),
+            Self::Server { endpoint, kind } => write!(f, "`{endpoint}` failed: {kind}"),
+            Self::Network { endpoint, source } => {
+                write!(f, "could not reach `{endpoint}`: {source}")
+            }
+            Self::Response { endpoint, source } => {
+                write!(f, "invalid response from `{endpoint}`: {source}")
+            }
+            Self::Io { path, source } => write!(f, "`{}`: {source}", path.display()),
+            Self::Config { path, message } => {
+                write!(f, "config file `{}`: {message}", path.display())
+            }
+            Self::Checksum {
+                path,
+                expected,
+                got,
+            } => write!(
+                f,
+                "`{path}` reads back with SHA-256 `{got}`, expected `{expected}`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Server { kind, .. } => Some(kind),
+            Self::Network { source, .. } => Some(source),
+            Self::Response { source, .. } => Some(source),
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Keeps errors that are already this type, anything else only keeps its message.
+impl From<Box<dyn StdError>> for Error {
+    fn from(e: Box<dyn StdError>) -> Self {
+        match e.downcast::<Self>() {
+            Ok(e) => *e,
+            Err(e) => Self::Other(e.to_string()),
+        }
+    }
+}
+
+/// The endpoint part of a `{instance}/api/...` url, such as `api/storage/v1/diritems`,
+/// leaving out the instance and any path or token after it.
+pub fn endpoint(url: &str) -> String {
+    match url.find("/api/") {
+        Some(start) => url[start + 1..]
+            .split('/')
+            .take(4)
+            .collect::<Vec<_>>()
+            .join("/"),
+        None => url.to_string(),
     }
 }
 
-impl std::error::Error for Error {}
+fn v1_exit_code(kind: &V1Error) -> i32 {
+    match kind {
+        V1Error::InvalidToken => EXIT_INVALID_TOKEN,
+        V1Error::CompileError { .. } => EXIT_COMPILE,
+        _ => EXIT_SERVER,
+    }
+}
+
+/// The exit code for an error returned by a command.
+pub fn exit_code(e: &(dyn StdError + 'static)) -> i32 {
+    if let Some(e) = e.downcast_ref::<Error>() {
+        e.exit_code()
+    } else if let Some(kind) = e.downcast_ref::<V1Error>() {
+        v1_exit_code(kind)
+    } else if e.is::<Unknown>() {
+        EXIT_USAGE
+    } else if e.is::<reqwest::Error>() {
+        EXIT_NETWORK
+    } else if e.is::<serde_json::Error>() {
+        EXIT_SERVER
+    } else if e.is::<io::Error>() {
+        EXIT_IO
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(kind: V1Error) -> Error {
+        Error::server("gm.example.com/api/storage/v1/diritems/token/tex", kind)
+    }
+
+    fn network() -> Error {
+        let e = reqwest::blocking::Client::new()
+            .get("not a url")
+            .build()
+            .unwrap_err();
+        Error::network("gm.example.com/api/storage/v1/mkdir", e)
+    }
+
+    fn response() -> Error {
+        let e = serde_json::from_str::<u8>("x").unwrap_err();
+        Error::response("gm.example.com/api/storage/v1/mkdir", e)
+    }
+
+    fn io() -> Error {
+        Error::io(Path::new("a.txt"), io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    #[test]
+    fn variant_exit_codes() {
+        let cases = [
+            (Error::Usage("missing argument".to_string()), EXIT_USAGE),
+            (Error::NotLoggedIn, EXIT_NOT_LOGGED_IN),
+            (server(V1Error::InvalidToken), EXIT_INVALID_TOKEN),
+            (
+                server(V1Error::CompileError {
+                    content: "! Undefined control sequence.".to_string(),
+                }),
+                EXIT_COMPILE,
+            ),
+            (server(V1Error::FileNotFound), EXIT_SERVER),
+            (network(), EXIT_NETWORK),
+            (response(), EXIT_SERVER),
+            (io(), EXIT_IO),
+            (
+                Error::config(PathBuf::from("accounts.yml"), "bad"),
+                EXIT_CONFIG,
+            ),
+            (
+                Error::Checksum {
+                    path: "/tex/paper.tex".to_string(),
+                    expected: "ab".to_string(),
+                    got: "cd".to_string(),
+                },
+                EXIT_SERVER,
+            ),
+            (
+                Error::Other("verification failed".to_string()),
+                EXIT_FAILURE,
+            ),
+        ];
+
+        for (e, code) in cases {
+            assert_eq!(e.exit_code(), code, "{}", e.kind());
+            let boxed: Box<dyn StdError> = e.into();
+            assert_eq!(exit_code(&*boxed), code);
+        }
+    }
+
+    #[test]
+    fn pinned_exit_codes() {
+        assert_eq!(server(V1Error::InvalidToken).exit_code(), 77);
+        assert_eq!(
+            server(V1Error::CompileError {
+                content: String::new()
+            })
+            .exit_code(),
+            65
+        );
+        assert_eq!(Error::Usage(String::new()).exit_code(), 64);
+        assert_eq!(Error::NotLoggedIn.exit_code(), 67);
+    }
+
+    #[test]
+    fn foreign_errors() {
+        let boxed: Box<dyn StdError> = Box::new(V1Error::InvalidToken);
+        assert_eq!(exit_code(&*boxed), EXIT_INVALID_TOKEN);
+
+        let boxed: Box<dyn StdError> = Box::new(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(exit_code(&*boxed), EXIT_IO);
+
+        let boxed: Box<dyn StdError> = serde_json::from_str::<u8>("x").unwrap_err().into();
+        assert_eq!(exit_code(&*boxed), EXIT_SERVER);
+
+        let boxed: Box<dyn StdError> = "something else".into();
+        assert_eq!(exit_code(&*boxed), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn boxed_errors_keep_their_variant() {
+        let boxed: Box<dyn StdError> = server(V1Error::FileNotFound).into();
+        assert!(matches!(
+            Error::from(boxed),
+            Error::Server {
+                kind: V1Error::FileNotFound,
+                ..
+            }
+        ));
+
+        let boxed: Box<dyn StdError> = "something else".into();
+        assert!(matches!(Error::from(boxed), Error::Other(message) if message == "something else"));
+    }
+
+    #[test]
+    fn endpoint_leaves_out_the_token() {
+        let message = server(V1Error::FileNotFound).to_string();
+        assert!(message.starts_with("`api/storage/v1/diritems` failed"));
+        assert!(!message.contains("token"));
+    }
+}
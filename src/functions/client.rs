@@ -0,0 +1,144 @@
+use std::{collections::HashMap, error::Error, fs, sync::OnceLock, time::Duration};
+
+use log::*;
+use reqwest::{
+    blocking::{Client, ClientBuilder},
+    Certificate, Identity, Proxy,
+};
+
+use crate::error::Error as CError;
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+static MAX_RETRIES: OnceLock<u32> = OnceLock::new();
+
+/// Defaults of the `connect-timeout`, `timeout` and `max-retries` flags, shown in help.
+pub const CONNECT_TIMEOUT: &str = "10";
+pub const TIMEOUT: &str = "30";
+pub const RETRIES: &str = "3";
+
+/// The client every request is sent with, built from default settings if `init_client` was never called.
+pub fn client() -> &'static Client {
+    CLIENT.get_or_init(|| {
+        client_builder(&HashMap::new())
+            .ok()
+            .and_then(|builder| builder.build().ok())
+            .unwrap_or_default()
+    })
+}
+
+/// How many times a request that failed for a transient reason is sent again.
+pub fn max_retries() -> u32 {
+    MAX_RETRIES
+        .get()
+        .copied()
+        .unwrap_or_else(|| RETRIES.parse().unwrap())
+}
+
+/// Builds the shared client from the `ca-file`, `client-cert`, `client-key`, `proxy`, `no-proxy`,
+/// `insecure-skip-verify`, `connect-timeout`, `timeout` and `max-retries` flags.
+pub fn init_client(map: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let client = client_builder(map)?.build()?;
+    if CLIENT.set(client).is_err() {
+        warn!("Client was already in use before its settings were loaded");
+    }
+
+    let retries = number(flag(map, "max-retries", RETRIES), "max-retries")?;
+    let _ = MAX_RETRIES.set(retries);
+    Ok(())
+}
+
+fn client_builder(map: &HashMap<String, String>) -> Result<ClientBuilder, Box<dyn Error>> {
+    let mut builder = Client::builder()
+        .connect_timeout(seconds(map, "connect-timeout", CONNECT_TIMEOUT)?)
+        .timeout(seconds(map, "timeout", TIMEOUT)?);
+
+    if let Some(path) = map.get("ca-file") {
+        debug!("Adding root certificates from `{path}`");
+        for cert in Certificate::from_pem_bundle(&fs::read(path)?)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    match (map.get("client-cert"), map.get("client-key")) {
+        (Some(cert), Some(key)) => {
+            debug!("Using client certificate `{cert}`");
+            let identity = Identity::from_pkcs8_pem(&fs::read(cert)?, &fs::read(key)?)?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => {
+            error!("Client certificates need both `--client-cert` and `--client-key`");
+            return Err(CError::Usage("incomplete client certificate".to_string()).into());
+        }
+    }
+
+    if map.contains_key("no-proxy") {
+        builder = builder.no_proxy();
+    }
+    if let Some(proxy) = map.get("proxy") {
+        debug!("Sending requests through proxy `{proxy}`");
+        builder = builder.proxy(Proxy::all(proxy)?);
+    }
+
+    if map.contains_key("insecure-skip-verify") {
+        warn!("Certificate verification is disabled, only use this with test instances");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    Ok(builder)
+}
+
+/// A timeout flag in seconds, where `0` waits forever.
+fn seconds(
+    map: &HashMap<String, String>,
+    name: &str,
+    default: &str,
+) -> Result<Option<Duration>, CError> {
+    let secs: u64 = number(flag(map, name, default), name)?;
+    Ok(Some(secs)
+        .filter(|secs| *secs != 0)
+        .map(Duration::from_secs))
+}
+
+fn flag<'a>(map: &'a HashMap<String, String>, name: &str, default: &'a str) -> &'a str {
+    map.get(name).map(String::as_str).unwrap_or(default)
+}
+
+fn number<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, CError> {
+    value.parse().map_err(|_| {
+        error!("`--{name}` takes a whole number");
+        CError::Usage(format!("invalid value `{value}` for `{name}`"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeout(value: Option<&str>) -> Option<Duration> {
+        let map = value
+            .map(|value| HashMap::from([("timeout".to_string(), value.to_string())]))
+            .unwrap_or_default();
+        seconds(&map, "timeout", TIMEOUT).unwrap()
+    }
+
+    #[test]
+    fn unset_timeout_is_finite() {
+        assert_eq!(timeout(None), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn zero_timeout_waits_forever() {
+        assert_eq!(timeout(Some("0")), None);
+        assert_eq!(timeout(Some("5")), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn negative_timeout_is_a_usage_error() {
+        let map = HashMap::from([("timeout".to_string(), "-1".to_string())]);
+        assert!(matches!(
+            seconds(&map, "timeout", TIMEOUT),
+            Err(CError::Usage(_))
+        ));
+    }
+}
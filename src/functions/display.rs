@@ -7,6 +7,7 @@ use goodmorning_bindings::structs::{
 };
 use log::*;
 
+use crate::config::InstancesConfig;
 use crate::error::Error as CError;
 
 pub fn display_profile(
@@ -25,7 +26,11 @@ pub fn display_profile(
         .unwrap()
         .format("%Y-%m-%d")
         .to_string();
-    let pfp = format!("https://{instance}/api/generic/v1/pfp/id/{}", account.id);
+    let pfp = InstancesConfig::endpoint(
+        &format!("{instance}/api/generic/v1/pfp/id/{}", account.id),
+        false,
+    )
+    .url;
     let description = if profile.description.is_empty() {
         "\x1B[38;5;8m[empty string]\x1B[0m"
     } else {
@@ -51,7 +56,8 @@ Details
 }
 
 pub fn display_profile_only(profile: &ProfileCustomisable, id: i64, instance: &str) -> String {
-    let pfp = format!("https://{instance}/api/generic/v1/pfp/id/{}", id);
+    let pfp =
+        InstancesConfig::endpoint(&format!("{instance}/api/generic/v1/pfp/id/{id}"), false).url;
     let description = if profile.description.is_empty() {
         "\x1B[38;5;8m[empty string]\x1B[0m"
     } else {
@@ -115,7 +121,7 @@ pub fn details_list() -> String {
 pub fn details_prompt(index: usize) -> Result<String, Box<dyn Error>> {
     if index > 7 || index == 0 {
         error!("{index} is not a valid option");
-        return Err(CError::StrErr("invalid option").into());
+        return Err(CError::Usage("invalid option".to_string()).into());
     }
 
     Ok(format!("Enter your {}", DETAILS_PROMPTS[index - 1]))
@@ -141,14 +147,16 @@ pub fn details_to_string(detail: &ProfileDetail) -> String {
 pub fn details_from_string(index: usize, value: String) -> Result<ProfileDetail, Box<dyn Error>> {
     if index > 7 || index == 0 {
         error!("{index} is not a valid option");
-        return Err(CError::StrErr("invalid option").into());
+        return Err(CError::Usage("invalid option".to_string()).into());
     }
 
     Ok(match index {
         1 => {
             let (day, month) = match value.split_once('/') {
                 Some(value) => value,
-                None => return Err(CError::StrErr("expected pattern `day/month`").into()),
+                None => {
+                    return Err(CError::Usage("expected pattern `day/month`".to_string()).into())
+                }
             };
 
             ProfileDetail::CakeDay {
@@ -161,7 +169,7 @@ pub fn details_from_string(index: usize, value: String) -> Result<ProfileDetail,
         2 => {
             let splitted = value.splitn(3, '/').collect::<Vec<_>>();
             if splitted.len() != 3 {
-                return Err(CError::StrErr("expected pattern `day/month/year`").into());
+                return Err(CError::Usage("expected pattern `day/month/year`".to_string()).into());
             }
 
             ProfileDetail::BirthDay {
@@ -224,7 +232,7 @@ pub fn contacts_list() -> String {
 pub fn contacts_prompt(index: usize) -> Result<String, Box<dyn Error>> {
     if index > 13 || index == 0 {
         error!("{index} is not a valid option");
-        return Err(CError::StrErr("invalid option").into());
+        return Err(CError::Usage("invalid option".to_string()).into());
     }
     Ok(format!("Enter your {}", CONTACTS_PROMPTS[index - 1]))
 }
@@ -260,7 +268,7 @@ pub fn contacts_to_string(contact: &ContactDetail) -> String {
 pub fn contacts_from_string(index: usize, value: String) -> Result<ContactDetail, Box<dyn Error>> {
     if index > 13 || index == 0 {
         error!("{index} is not a valid option");
-        return Err(CError::StrErr("invalid option").into());
+        return Err(CError::Usage("invalid option".to_string()).into());
     }
 
     Ok(match index {
@@ -268,9 +276,10 @@ pub fn contacts_from_string(index: usize, value: String) -> Result<ContactDetail
             let (name, instance) = match value.split_once('@') {
                 Some((name, instance)) => (name.to_string(), instance.to_string()),
                 None => {
-                    return Err(
-                        CError::StrErr("invalid format, expected `username@instance.com`").into(),
+                    return Err(CError::Usage(
+                        "invalid format, expected `username@instance.com`".to_string(),
                     )
+                    .into())
                 }
             };
             ContactDetail::Email { name, instance }
@@ -279,9 +288,10 @@ pub fn contacts_from_string(index: usize, value: String) -> Result<ContactDetail
             let (name, instance) = match value.split_once(':') {
                 Some((name, instance)) => (name.to_string(), instance.to_string()),
                 None => {
-                    return Err(
-                        CError::StrErr("invalid format, expected `username@instance.com`").into(),
+                    return Err(CError::Usage(
+                        "invalid format, expected `username@instance.com`".to_string(),
                     )
+                    .into())
                 }
             };
             ContactDetail::Matrix { name, instance }
@@ -290,9 +300,10 @@ pub fn contacts_from_string(index: usize, value: String) -> Result<ContactDetail
             let (name, instance) = match value.split_once(':') {
                 Some((name, instance)) => (name.to_string(), instance.to_string()),
                 None => {
-                    return Err(
-                        CError::StrErr("invalid format, expected `username@instance.com`").into(),
+                    return Err(CError::Usage(
+                        "invalid format, expected `username@instance.com`".to_string(),
                     )
+                    .into())
                 }
             };
             ContactDetail::Mastodon { name, instance }
@@ -301,9 +312,10 @@ pub fn contacts_from_string(index: usize, value: String) -> Result<ContactDetail
             let (name, instance) = match value.split_once(':') {
                 Some((name, instance)) => (name.to_string(), instance.to_string()),
                 None => {
-                    return Err(
-                        CError::StrErr("invalid format, expected `username@instance.com`").into(),
+                    return Err(CError::Usage(
+                        "invalid format, expected `username@instance.com`".to_string(),
                     )
+                    .into())
                 }
             };
             ContactDetail::Lemmy { name, instance }
@@ -319,9 +331,10 @@ pub fn contacts_from_string(index: usize, value: String) -> Result<ContactDetail
             let (name, discriminator) = match value.split_once(':') {
                 Some((name, instance)) => (name.to_string(), instance.parse()?),
                 None => {
-                    return Err(
-                        CError::StrErr("invalid format, expected `username@instance.com`").into(),
+                    return Err(CError::Usage(
+                        "invalid format, expected `username@instance.com`".to_string(),
                     )
+                    .into())
                 }
             };
             ContactDetail::Odysee {
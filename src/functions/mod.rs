@@ -1,19 +1,23 @@
-mod args_parse;
+mod client;
 mod diritem_tostring;
 mod display;
 mod display_publish_item;
-mod map_args;
 mod path;
 mod prompt;
+mod relogin;
 mod request;
+mod seal;
+mod suggest;
 mod yes;
 
-pub use args_parse::args_parse;
+pub use client::*;
 pub use diritem_tostring::*;
 pub use display::*;
 pub use display_publish_item::*;
-pub use map_args::*;
 pub use path::*;
 pub use prompt::*;
+pub use relogin::*;
 pub use request::*;
+pub use seal::*;
+pub use suggest::*;
 pub use yes::*;
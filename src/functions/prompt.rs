@@ -1,59 +1,107 @@
 use std::{
-    collections::HashMap,
-    io::{stdin, stdout, Write},
+    error::Error,
+    io::{stderr, stdin, IsTerminal, Write},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 use rpassword::read_password;
 
-pub fn prompt(s: &str) -> String {
+use crate::args::Args;
+use crate::error::Error as CError;
+
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+
+/// Stops every prompt for the rest of the run, as `--no-input` does.
+pub fn disable_input() {
+    NO_INPUT.store(true, Ordering::Relaxed);
+}
+
+/// Whether the user can be asked for input: not with `--no-input`, or when stdin is not a terminal.
+pub fn interactive() -> bool {
+    !NO_INPUT.load(Ordering::Relaxed) && stdin().is_terminal()
+}
+
+/// Fails with a usage error naming the argument when it can't be prompted for.
+pub fn require_input(name: &str) -> Result<(), Box<dyn Error>> {
+    if interactive() {
+        return Ok(());
+    }
+
+    Err(CError::Usage(format!(
+        "missing argument `{name}`, pass it on the command line when input is disabled"
+    ))
+    .into())
+}
+
+pub fn prompt(s: &str) -> Result<String, Box<dyn Error>> {
+    require_input(if s.is_empty() { "value" } else { s })?;
+
     if s.is_empty() {
-        print!("Enter a value: ");
+        eprint!("Enter a value: ");
     } else {
-        print!("{s}: ");
+        eprint!("{s}: ");
     }
 
-    stdout().flush().unwrap();
+    stderr().flush().unwrap();
 
     let mut buf = String::new();
     stdin().read_line(&mut buf).unwrap();
-    buf.trim().to_string()
+    Ok(buf.trim().to_string())
 }
 
-pub fn prompt_not_present(msg: &str, key: &str, map: &mut HashMap<String, String>) {
-    match map.get(key) {
-        Some(val) => println!("{msg}: {val}"),
+pub fn prompt_not_present(msg: &str, key: &str, map: &mut Args) -> Result<(), Box<dyn Error>> {
+    match map.opt(key) {
+        Some(val) => eprintln!("{msg}: {val}"),
         None => {
-            map.insert(key.to_string(), prompt(msg));
+            require_input(key)?;
+            map.set(key, prompt(msg)?);
         }
     }
+    Ok(())
 }
 
-pub fn prompt_cmd() -> Vec<String> {
-    print!("> ");
-    stdout().flush().unwrap();
+/// Reads a line of an interactive shell such as `fs`.
+pub fn prompt_cmd() -> Result<Vec<String>, Box<dyn Error>> {
+    if !interactive() {
+        return Err(CError::Usage(
+            "this command reads commands from a terminal, and input is disabled".to_string(),
+        )
+        .into());
+    }
+
+    eprint!("> ");
+    stderr().flush().unwrap();
 
     let mut buf = String::new();
     stdin().read_line(&mut buf).unwrap();
-    buf.trim().split(' ').map(str::to_string).collect()
+    Ok(buf.trim().split(' ').map(str::to_string).collect())
 }
 
-pub fn prompt_password(s: &str) -> String {
+pub fn prompt_password(s: &str) -> Result<String, Box<dyn Error>> {
+    require_input(if s.is_empty() { "password" } else { s })?;
+
     if s.is_empty() {
-        print!("Password: ");
+        eprint!("Password: ");
     } else {
-        print!("{s}: ");
+        eprint!("{s}: ");
     }
 
-    stdout().flush().unwrap();
+    stderr().flush().unwrap();
 
-    read_password().unwrap()
+    Ok(read_password().unwrap())
 }
 
-pub fn prompt_password_not_present(msg: &str, key: &str, map: &mut HashMap<String, String>) {
-    match map.get(key) {
-        Some(_val) => println!("{msg}:"),
+pub fn prompt_password_not_present(
+    msg: &str,
+    key: &str,
+    map: &mut Args,
+) -> Result<(), Box<dyn Error>> {
+    match map.opt(key) {
+        Some(_val) => eprintln!("{msg}:"),
         None => {
-            map.insert(key.to_string(), prompt_password(msg));
+            require_input(key)?;
+            map.set(key, prompt_password(msg)?);
         }
     }
+    Ok(())
 }
@@ -0,0 +1,60 @@
+use std::error::Error;
+
+use goodmorning_bindings::services::v1::V1Error;
+use log::*;
+
+use crate::api::GmClient;
+use crate::config::AccountConfig;
+use crate::error::Error as CError;
+use crate::functions::{interactive, prompt, prompt_password};
+use crate::traits::ConfigTriat;
+
+/// Asks to log in again after the server rejected the saved token of `client` in a request to `url`,
+/// returns the new token. Set as the `RenewToken` of clients made by commands.
+///
+/// When input is disabled, the `InvalidToken` error is returned, exiting with `EXIT_INVALID_TOKEN`.
+pub fn relogin(client: &GmClient, url: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let token = client.token();
+    let mut config = AccountConfig::load()?;
+    let name = match config
+        .accounts
+        .iter()
+        .find(|(_, account)| account.token == token)
+    {
+        Some((name, _)) => name.clone(),
+        None => return Ok(None),
+    };
+
+    error!("The server rejected the token saved as `{name}`");
+    if !interactive() {
+        info!("It may have been regenerated elsewhere, run `login` again");
+        return Err(CError::server(url, V1Error::InvalidToken).into());
+    }
+
+    let account = config.accounts.get_mut(&name).unwrap();
+    let username = if account.username.is_empty() {
+        prompt("Username")?
+    } else {
+        account.username.clone()
+    };
+
+    let answer = prompt(&format!(
+        "Log in again as `{username}:{}`? (y/N)",
+        account.instance
+    ))?;
+    if !answer.eq_ignore_ascii_case("y") {
+        return Ok(None);
+    }
+
+    let (id, token) = GmClient::new(&account.instance, client.http)
+        .login(&username, &prompt_password("Password")?)
+        .inspect_err(|_| error!("Login failed"))?;
+
+    account.token = token.clone();
+    account.id = id;
+    account.username = username;
+    config.save()?;
+
+    info!("Logged in again, new token saved as `{name}`");
+    Ok(Some(token))
+}
@@ -1,68 +1,156 @@
-use std::{env, error::Error};
+use std::{error::Error, thread, time::Duration};
 
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
 use log::*;
-use reqwest::header::USER_AGENT;
-use serde::{de::DeserializeOwned, Serialize};
+use reqwest::{
+    blocking::{multipart::Form, RequestBuilder, Response},
+    header::{RETRY_AFTER, USER_AGENT},
+    Method, StatusCode,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 
-pub fn post<R: DeserializeOwned, T: Serialize + Sized>(
-    url: &str,
-    body: T,
-    http: bool,
-) -> Result<R, Box<dyn Error>> {
-    if http {
+use crate::config::InstancesConfig;
+use crate::error::{endpoint, Error as CError};
+use crate::functions::{client, max_retries};
+
+/// What to send along with a request.
+pub enum Body<'a> {
+    Empty,
+    Json(Value),
+    /// Forms cannot be cloned, so a new one is built for every attempt.
+    Form(&'a dyn Fn() -> Result<Form, Box<dyn Error>>),
+}
+
+/// Starts a request to a `{instance}/api/...` path, applying the instance's connection settings.
+pub fn request(method: Method, url: &str, http: bool) -> RequestBuilder {
+    let endpoint = InstancesConfig::endpoint(url, http);
+    if endpoint.is_http() {
         warn!("This request is sent using the insecure http protocol");
     }
 
-    info!("Sending request");
-    let res = reqwest::blocking::Client::new()
-        .post(format!("{}://{url}", if http { "http" } else { "https" }))
-        .header(USER_AGENT, &format!("gm-cli {}", env!("CARGO_PKG_VERSION")))
-        .json(&body)
-        .send();
+    let builder = client()
+        .request(method, &endpoint.url)
+        .header(USER_AGENT, endpoint.user_agent);
+    match endpoint.timeout {
+        Some(timeout) => builder.timeout(timeout),
+        None => builder,
+    }
+}
+
+/// Longest `Retry-After` worth waiting for, a longer one is returned as it is.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(10);
+
+/// Sends a request and reads the whole response, sending it again up to `max_retries` times
+/// if it failed for a transient reason.
+///
+/// Only a failed connection is retried for every method, as nothing of the request was sent yet.
+/// Timeouts, `429`, `502` and `503` are only retried for methods that are safe to repeat, since
+/// the body of an upload may already have been sent, in part or in full.
+pub fn send(
+    method: Method,
+    url: &str,
+    body: &Body,
+    http: bool,
+) -> Result<(StatusCode, Vec<u8>), Box<dyn Error>> {
+    let idempotent = matches!(
+        method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    );
+    let mut retry = 0;
+
+    let res = loop {
+        info!("Sending request");
+        let builder = request(method.clone(), url, http);
+        let builder = match body {
+            Body::Empty => builder,
+            Body::Json(value) => builder.json(value),
+            Body::Form(form) => builder.multipart(form()?),
+        };
+
+        let res = builder.send();
+        let wait = match &res {
+            _ if retry >= max_retries() => None,
+            Ok(res) => transient(res, idempotent, retry),
+            Err(e) if e.is_connect() || (idempotent && e.is_timeout()) => Some(backoff(retry)),
+            Err(_) => None,
+        };
+        let Some(wait) = wait else {
+            break res;
+        };
+
+        match res {
+            Ok(res) => warn!("Server responded with code `{}`", res.status().as_u16()),
+            Err(e) => warn!("{}", CError::network(url, e)),
+        }
+        retry += 1;
+        info!(
+            "Retrying in {:.1}s ({retry}/{})",
+            wait.as_secs_f32(),
+            max_retries()
+        );
+        thread::sleep(wait);
+    };
+
     let res = match res {
         Ok(res) => res,
         Err(e) => {
-            error!("Error sending request to `{url}`");
-            return Err(crate::error::Error::StringErr(e.to_string()).into());
+            error!("Error sending request to `{}`", endpoint(url));
+            return Err(CError::network(url, e).into());
         }
     };
-    debug!("Response recieved, deserializing");
-    let text = res.text().unwrap_or_default();
-    match serde_json::from_str(&text) {
-        Ok(out) => Ok(out),
-        Err(e) => {
-            error!("Deserialization failed");
-            info!("Server response: \n{}", text);
-            Err(crate::error::Error::StringErr(e.to_string()).into())
-        }
-    }
+    debug!("Response recieved");
+    let status = res.status();
+    let bytes = res.bytes().map_err(|e| CError::network(url, e))?;
+    Ok((status, bytes.to_vec()))
 }
 
-pub fn get<R: DeserializeOwned>(url: &str, http: bool) -> Result<R, Box<dyn Error>> {
-    if http {
-        warn!("This request is sent using the insecure http protocol");
+/// How long to wait before sending a request again after this response, `None` if it shouldn't be.
+fn transient(res: &Response, idempotent: bool, retry: u32) -> Option<Duration> {
+    match res.status() {
+        StatusCode::TOO_MANY_REQUESTS
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+            if idempotent => {}
+        _ => return None,
     }
 
-    info!("Sending request");
-    let res = reqwest::blocking::Client::new()
-        .get(format!("{}://{url}", if http { "http" } else { "https" }))
-        .header(USER_AGENT, &format!("gm-cli {}", env!("CARGO_PKG_VERSION")))
-        .send();
-    let res = match res {
-        Ok(res) => res,
-        Err(e) => {
-            error!("Error sending request to `{url}`");
-            return Err(crate::error::Error::StringErr(e.to_string()).into());
+    let retry_after = res
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok()?.trim().parse().ok())
+        .map(Duration::from_secs);
+    match retry_after {
+        Some(wait) if wait > MAX_RETRY_AFTER => {
+            warn!("Server asked to retry in {}s, not waiting", wait.as_secs());
+            None
         }
-    };
-    debug!("Response recieved, deserializing");
-    let text = res.text().unwrap_or_default();
-    match serde_json::from_str(&text) {
+        Some(wait) => Some(wait),
+        None => Some(backoff(retry)),
+    }
+}
+
+/// Doubles with every retry up to `BACKOFF_MAX`, the upper half of it chosen at random
+/// so clients that failed together don't retry together.
+fn backoff(retry: u32) -> Duration {
+    let wait = BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(retry))
+        .min(BACKOFF_MAX);
+    let half = wait.as_millis() as u64 / 2;
+    let jitter = OsRng.next_u64() % (half + 1);
+    Duration::from_millis(half + jitter)
+}
+
+pub fn deserialize<R: DeserializeOwned>(url: &str, bytes: &[u8]) -> Result<R, Box<dyn Error>> {
+    debug!("Deserializing response");
+    match serde_json::from_slice(bytes) {
         Ok(out) => Ok(out),
         Err(e) => {
             error!("Deserialization failed");
-            info!("Server response: \n{}", text);
-            Err(crate::error::Error::StringErr(e.to_string()).into())
+            info!("Server response: \n{}", String::from_utf8_lossy(bytes));
+            Err(CError::response(url, e).into())
         }
     }
 }
@@ -0,0 +1,124 @@
+use std::{env, error::Error, sync::OnceLock};
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use log::*;
+
+use crate::error::Error as CError;
+use crate::functions::{interactive, prompt_password};
+
+/// Passphrase used directly, skipping the prompt.
+pub const PASSPHRASE_ENV: &str = "GM_PASSPHRASE";
+/// Unix socket of an agent that replies to `passphrase\n` with the passphrase on one line.
+pub const AGENT_SOCK_ENV: &str = "GM_AGENT_SOCK";
+
+const NONCE_LEN: usize = 24;
+
+static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+
+pub fn new_salt() -> String {
+    let mut salt = [0; 16];
+    OsRng.fill_bytes(&mut salt);
+    STANDARD.encode(salt)
+}
+
+/// Derives the key for `salt` once per session, asking for the passphrase if needed.
+pub fn unlock(salt: &str) -> Result<&'static [u8; 32], Box<dyn Error>> {
+    if let Some(key) = KEY.get() {
+        return Ok(key);
+    }
+
+    let passphrase = passphrase()?;
+    let _ = KEY.set(derive_key(&passphrase, salt)?);
+    Ok(KEY.get().unwrap())
+}
+
+/// Sets the session key from a new passphrase, used when encryption is first turned on.
+pub fn unlock_with(passphrase: &str, salt: &str) -> Result<(), Box<dyn Error>> {
+    let key = derive_key(passphrase, salt)?;
+    if KEY.set(key).is_err() && KEY.get() != Some(&key) {
+        return Err(CError::Other("a different passphrase is already in use".to_string()).into());
+    }
+    Ok(())
+}
+
+pub fn seal(token: &str, key: &[u8; 32]) -> Result<String, Box<dyn Error>> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, token.as_bytes())
+        .map_err(|_| CError::Other("failed to encrypt token".to_string()))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(STANDARD.encode(sealed))
+}
+
+pub fn unseal(sealed: &str, key: &[u8; 32]) -> Result<String, Box<dyn Error>> {
+    let sealed = STANDARD.decode(sealed)?;
+    if sealed.len() < NONCE_LEN {
+        return Err(CError::Other("encrypted token is truncated".to_string()).into());
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let token = cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| CError::Other("wrong passphrase or corrupted token".to_string()))?;
+
+    Ok(String::from_utf8(token)?)
+}
+
+fn derive_key(passphrase: &str, salt: &str) -> Result<[u8; 32], Box<dyn Error>> {
+    let salt = STANDARD.decode(salt)?;
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| CError::Other(format!("failed to derive key: {e}")))?;
+    Ok(key)
+}
+
+fn passphrase() -> Result<String, Box<dyn Error>> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        debug!("Using passphrase from `{PASSPHRASE_ENV}`");
+        return Ok(passphrase);
+    }
+
+    if let Ok(path) = env::var(AGENT_SOCK_ENV) {
+        debug!("Asking agent at `{path}` for passphrase");
+        match agent_passphrase(&path) {
+            Ok(passphrase) => return Ok(passphrase),
+            Err(e) => warn!("Could not get passphrase from agent: {e}"),
+        }
+    }
+
+    if !interactive() {
+        return Err(CError::Usage(format!(
+            "saved tokens are encrypted, set `{PASSPHRASE_ENV}` or `{AGENT_SOCK_ENV}` when input is disabled"
+        ))
+        .into());
+    }
+    prompt_password("Passphrase to unlock your tokens")
+}
+
+#[cfg(unix)]
+fn agent_passphrase(path: &str) -> Result<String, Box<dyn Error>> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path)?;
+    stream.write_all(b"passphrase\n")?;
+
+    let mut passphrase = String::new();
+    BufReader::new(stream).read_line(&mut passphrase)?;
+    Ok(passphrase.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(not(unix))]
+fn agent_passphrase(_path: &str) -> Result<String, Box<dyn Error>> {
+    Err(CError::Other("agent sockets are only supported on unix".to_string()).into())
+}
@@ -0,0 +1,80 @@
+/// Names from `candidates` close to `word`, closest first.
+///
+/// Close means a few typos or swapped letters away, or starting with `word` if it is long enough.
+pub fn suggest<S: AsRef<str>>(word: &str, candidates: impl IntoIterator<Item = S>) -> Vec<String> {
+    let limit = (word.chars().count() / 3).max(1);
+
+    let mut close = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let candidate = candidate.as_ref();
+            let distance = distance(word, candidate);
+            if distance <= limit || (word.len() >= 3 && candidate.starts_with(word)) {
+                Some((distance, candidate.to_string()))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    close.sort();
+    close.dedup();
+    close.into_iter().take(3).map(|(_, name)| name).collect()
+}
+
+/// Edits needed to turn `a` into `b`, where swapping two neighbouring letters counts as one.
+fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_counts_edits() {
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("", "ls"), 2);
+        assert_eq!(distance("ls", "ls"), 0);
+        assert_eq!(distance("lss", "ls"), 1);
+        assert_eq!(distance("mv", "cv"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn swap_counts_as_one() {
+        assert_eq!(distance("sl", "ls"), 1);
+        assert_eq!(distance("uplaod", "upload"), 1);
+        assert_eq!(distance("ab", "ba"), distance("ba", "ab"));
+    }
+
+    #[test]
+    fn letters_not_bytes() {
+        assert_eq!(distance("é", "e"), 1);
+    }
+
+    #[test]
+    fn suggestions_closest_first() {
+        let commands = ["upload", "unpack", "unqueue", "ls"];
+        assert_eq!(suggest("uplaod", commands), ["upload"]);
+        assert_eq!(suggest("sl", commands), ["ls"]);
+        assert!(suggest("xyzzy", commands).is_empty());
+    }
+}
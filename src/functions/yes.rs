@@ -1,41 +1,51 @@
-use std::{collections::HashMap, io::stdin, process};
+use std::{error::Error, io::stdin};
 
-pub fn yes(map: &HashMap<String, String>) {
-    println!("Are you sure you want to do that?");
-    println!("(Press enter to continue, Ctrl + C to exit)");
+use crate::args::Args;
+use crate::error::Error as CError;
+use crate::functions::interactive;
 
-    if map.get("yes").is_some() {
-        println!();
-        return;
-    }
-
-    stdin().read_line(&mut String::new()).unwrap();
+pub fn yes(map: &Args) -> Result<(), Box<dyn Error>> {
+    yes_msg("Are you sure you want to do that?", map)
 }
 
-pub fn yes_msg(s: &str, map: &HashMap<String, String>) {
-    println!("{s}");
-    println!("(Press enter to continue, Ctrl + C to exit)");
+pub fn yes_msg(s: &str, map: &Args) -> Result<(), Box<dyn Error>> {
+    eprintln!("{s}");
+    eprintln!("(Press enter to continue, Ctrl + C to exit)");
 
-    if map.get("yes").is_some() {
-        println!();
-        return;
+    if map.flag("yes") {
+        eprintln!();
+        return Ok(());
     }
+    require_yes()?;
+
     stdin().read_line(&mut String::new()).unwrap();
+    Ok(())
 }
 
-pub fn type_yes(map: &HashMap<String, String>) {
-    println!("Are you sure you want to do that?");
-    println!("(Type \"yes\" to continue, Ctrl + C to exit)");
+pub fn type_yes(map: &Args) -> Result<(), Box<dyn Error>> {
+    eprintln!("Are you sure you want to do that?");
+    eprintln!("(Type \"yes\" to continue, Ctrl + C to exit)");
 
-    if map.get("yes").is_some() {
-        println!();
-        return;
+    if map.flag("yes") {
+        eprintln!();
+        return Ok(());
     }
+    require_yes()?;
 
     let mut input = String::new();
 
     stdin().read_line(&mut input).unwrap();
     if input.trim() != "yes" {
-        process::exit(-1);
+        return Err(CError::Other("cancelled, `yes` was not typed".to_string()).into());
     }
+    Ok(())
+}
+
+/// Confirmations can't be answered without input, so they need `--yes` instead.
+fn require_yes() -> Result<(), Box<dyn Error>> {
+    if interactive() {
+        return Ok(());
+    }
+
+    Err(CError::Usage("confirmation needed, add `--yes` when input is disabled".to_string()).into())
 }
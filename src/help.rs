@@ -0,0 +1,376 @@
+use std::{error::Error, fmt::Write};
+
+use crate::args::{global_takes_value, is_short, Arg, Kind, Spec, GLOBAL};
+use crate::commands::{aliases, sections};
+use crate::error::Error as CError;
+use crate::traits::config_dir;
+use crate::Command;
+
+const BIN: &str = env!("CARGO_PKG_NAME");
+
+const TITLE: &str = "\x1b[32m";
+const SECTION: &str = "\x1b[91m";
+const NAME: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// `help` without its colours, for json and yaml output.
+pub fn plain(help: &str) -> String {
+    [TITLE, SECTION, NAME, RESET]
+        .iter()
+        .fold(help.to_string(), |help, code| help.replace(code, ""))
+}
+
+/// Help for the command named by the leading words of `args`, values and flags after it are ignored.
+/// Shows the overview if `args` names no command.
+pub fn help(root: &Command, args: &[String]) -> String {
+    let args = root.expand(args);
+    match resolve(root, &args) {
+        (path, _, _) if path.is_empty() => overview(),
+        (path, command, _) => detail(&path, command),
+    }
+}
+
+/// Like `help`, but every word must be part of a command path.
+pub fn help_strict(root: &Command, args: &[String]) -> Result<String, Box<dyn Error>> {
+    let args = root.expand(args);
+    match resolve(root, &args) {
+        (_, _, Some(word)) => Err(CError::Usage(format!("No such command `{word}`")).into()),
+        (path, _, None) if path.is_empty() => Ok(overview()),
+        (path, command, None) => Ok(detail(&path, command)),
+    }
+}
+
+/// Every command grouped by section, with the global flags.
+pub fn overview() -> String {
+    let sections = sections()
+        .into_iter()
+        .map(|(name, section)| (name, section.leaves()))
+        .collect::<Vec<_>>();
+    let width = sections
+        .iter()
+        .flat_map(|(_, leaves)| leaves.iter().map(|(path, spec)| synopsis(path, spec).len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut out = format!("{TITLE}GM CLI commands{RESET}\n");
+    for (name, leaves) in sections {
+        let _ = write!(out, "\n{SECTION}{}:{RESET}\n", name.to_uppercase());
+        for (path, spec) in leaves {
+            let _ = writeln!(
+                out,
+                "    {NAME}{:width$}{RESET}    {}",
+                synopsis(&path, spec),
+                spec.about
+            );
+        }
+    }
+
+    out.push_str(&flags_section("GLOBAL FLAGS", GLOBAL));
+    let _ = write!(
+        out,
+        "\nRun `{BIN} help [command]` or `{BIN} [command] --help` for arguments, flags, examples and aliases.
+Names from older versions, such as `ls` for `storage ls`, still work as aliases."
+    );
+    out
+}
+
+fn detail(path: &[&str], command: &Command) -> String {
+    let spec = match command {
+        Command::Command(spec) => spec,
+        Command::Category(_) => {
+            let leaves = command.leaves();
+            let width = leaves
+                .iter()
+                .map(|(sub, spec)| synopsis(&[path, sub.as_slice()].concat(), spec).len())
+                .max()
+                .unwrap_or_default();
+
+            let mut out = format!(
+                "{TITLE}{BIN} {}{RESET}\n\n{SECTION}COMMANDS:{RESET}\n",
+                path.join(" ")
+            );
+            for (sub, spec) in leaves {
+                let _ = writeln!(
+                    out,
+                    "    {NAME}{:width$}{RESET}    {}",
+                    synopsis(&[path, sub.as_slice()].concat(), spec),
+                    spec.about
+                );
+            }
+            return out.trim_end().to_string();
+        }
+    };
+
+    let mut out = format!(
+        "{TITLE}{BIN} {}{RESET} - {}\n\n{SECTION}USAGE:{RESET}\n    {BIN} {} {}\n",
+        path.join(" "),
+        spec.about,
+        path.join(" "),
+        spec.usage()
+    );
+    if spec.login {
+        out.push_str("\nNeeds a logged in account.\n");
+    }
+
+    let positional = spec.positional().copied().collect::<Vec<_>>();
+    let flags = spec
+        .args
+        .iter()
+        .filter(|arg| !arg.positional)
+        .copied()
+        .collect::<Vec<_>>();
+    out.push_str(&flags_section("ARGUMENTS", &positional));
+    out.push_str(&flags_section("FLAGS", &flags));
+
+    if !spec.examples.is_empty() {
+        let _ = write!(out, "\n{SECTION}EXAMPLES:{RESET}\n");
+        for example in spec.examples {
+            let _ = writeln!(out, "    {BIN} {example}");
+        }
+    }
+    let mut names = aliases()
+        .into_iter()
+        .filter(|(_, target)| *target == path.join(" "))
+        .map(|(alias, _)| alias)
+        .collect::<Vec<_>>();
+    if !names.is_empty() {
+        names.sort();
+        let _ = write!(
+            out,
+            "\n{SECTION}ALIASES:{RESET}\n    {}\n",
+            names.join(", ")
+        );
+    }
+    if !spec.related.is_empty() {
+        let _ = write!(
+            out,
+            "\n{SECTION}SEE ALSO:{RESET}\n    {}\n",
+            spec.related.join(", ")
+        );
+    }
+
+    let _ = write!(
+        out,
+        "\nArguments can also be given as `--name value`, see `{BIN} help` for global flags."
+    );
+    out
+}
+
+fn flags_section(title: &str, args: &[Arg]) -> String {
+    if args.is_empty() {
+        return String::new();
+    }
+
+    let width = args.iter().map(|arg| label(arg).len()).max().unwrap();
+    let mut out = format!("\n{SECTION}{title}:{RESET}\n");
+    for arg in args {
+        let _ = writeln!(
+            out,
+            "    {NAME}{:width$}{RESET}    {}",
+            label(arg),
+            describe(arg)
+        );
+    }
+    out
+}
+
+/// A man page in roff format, built from the same specs as `help`.
+pub fn manpage() -> String {
+    let mut out = format!(
+        ".TH {} 1 \"\" \"{BIN} {}\" \"User Commands\"
+.SH NAME
+{} \\- command line client for GM services
+.SH SYNOPSIS
+.B {}
+\\fIcommand\\fR [\\fIarguments\\fR] [\\fB\\-\\-flags\\fR]
+.SH DESCRIPTION
+Arguments in square brackets are required, and asked for when missing if the command can.
+Arguments in round brackets are optional.
+Every argument can also be given as \\fB\\-\\-name value\\fR or \\fB\\-\\-name=value\\fR.
+",
+        roff(&BIN.to_uppercase()),
+        env!("CARGO_PKG_VERSION"),
+        roff(BIN),
+        roff(BIN),
+    );
+
+    for (name, section) in sections() {
+        let _ = writeln!(out, ".SH \"{} COMMANDS\"", name.to_uppercase());
+        for (path, spec) in section.leaves() {
+            let _ = writeln!(
+                out,
+                ".TP\n.B {} {}\n{}",
+                roff(&path.join(" ")),
+                roff(&spec.usage()),
+                roff(spec.about)
+            );
+            if spec.login {
+                out.push_str("Needs a logged in account.\n");
+            }
+
+            let described = spec
+                .args
+                .iter()
+                .filter(|arg| !describe(arg).is_empty())
+                .collect::<Vec<_>>();
+            if described.is_empty() && spec.examples.is_empty() {
+                continue;
+            }
+
+            out.push_str(".RS\n");
+            for arg in described {
+                let _ = writeln!(
+                    out,
+                    ".TP\n.B {}\n{}",
+                    roff(&label(arg)),
+                    roff(&describe(arg))
+                );
+            }
+            if !spec.examples.is_empty() {
+                out.push_str(".PP\nExamples:\n.nf\n");
+                for example in spec.examples {
+                    let _ = writeln!(out, "{} {}", roff(BIN), roff(example));
+                }
+                out.push_str(".fi\n");
+            }
+            out.push_str(".RE\n");
+        }
+    }
+
+    let mut aliases = aliases().into_iter().collect::<Vec<_>>();
+    aliases.sort();
+    out.push_str(".SH ALIASES\n");
+    for (alias, target) in aliases {
+        let _ = writeln!(out, ".TP\n.B {}\n{}", roff(&alias), roff(&target));
+    }
+
+    out.push_str(".SH \"GLOBAL FLAGS\"\n");
+    for arg in GLOBAL {
+        let _ = writeln!(
+            out,
+            ".TP\n.B {}\n{}",
+            roff(&label(arg)),
+            roff(&describe(arg))
+        );
+    }
+
+    let _ = write!(
+        out,
+        ".SH FILES
+.TP
+.I {}
+Config files, the directory can be changed with \\fBGM_CONFIG_DIR\\fR.
+",
+        roff(&config_dir().to_string_lossy())
+    );
+    out
+}
+
+/// Finds the deepest command named by `args`, skipping flags and the values of global options.
+/// Also returns the first word that did not name a command, if any.
+fn resolve<'a>(
+    root: &'a Command,
+    args: &'a [String],
+) -> (Vec<&'static str>, &'a Command, Option<&'a String>) {
+    let mut current = root;
+    let mut path = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(flag) = arg.strip_prefix("--") {
+            if global_takes_value(flag) {
+                iter.next();
+            }
+            continue;
+        }
+        if is_short(arg) {
+            continue;
+        }
+
+        match current {
+            Command::Category(cog) => match cog.get_key_value(arg.as_str()) {
+                Some((name, command)) => {
+                    path.push(*name);
+                    current = command;
+                }
+                None => return (path, current, Some(arg)),
+            },
+            Command::Command(_) => return (path, current, Some(arg)),
+        }
+    }
+
+    (path, current, None)
+}
+
+/// Command path and its positional arguments, such as `cp [from] [to]`.
+fn synopsis(path: &[&str], spec: &Spec) -> String {
+    path.iter()
+        .map(|word| word.to_string())
+        .chain(spec.positional().map(Arg::usage))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn label(arg: &Arg) -> String {
+    let long = match (arg.positional, arg.kind.takes_value()) {
+        (true, _) => return arg.usage(),
+        (false, false) => format!("--{}", arg.name),
+        (false, true) => format!("--{} <{}>", arg.name, arg.name),
+    };
+    match arg.short {
+        Some(short) => format!("-{short}, {long}"),
+        None => long,
+    }
+}
+
+/// The help text of an argument, followed by what the spec says about it.
+fn describe(arg: &Arg) -> String {
+    let mut notes = Vec::new();
+    match arg.kind {
+        Kind::Int => notes.push("a number".to_string()),
+        Kind::Uint => notes.push("a whole number, 0 or more".to_string()),
+        Kind::Secret => notes.push("not echoed when prompted".to_string()),
+        Kind::Choice(choices) => notes.push(format!(
+            "one of {}",
+            choices
+                .iter()
+                .map(|choice| format!("`{choice}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Kind::Path => notes.push("a path in your storage".to_string()),
+        Kind::File => notes.push("a file on this device".to_string()),
+        Kind::Count => notes.push("may be repeated".to_string()),
+        Kind::Str | Kind::Switch => {}
+    }
+    if let Some(default) = arg.default {
+        notes.push(format!("defaults to `{default}`"));
+    } else if arg.required && arg.prompt.is_some() {
+        notes.push("asked for when missing".to_string());
+    } else if arg.positional && !arg.required {
+        notes.push("optional".to_string());
+    }
+
+    match (arg.help, notes.is_empty()) {
+        (help, true) => help.to_string(),
+        ("", false) => {
+            let notes = notes.join(", ");
+            let mut chars = notes.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        (help, false) => format!("{help} ({})", notes.join(", ")),
+    }
+}
+
+/// Escapes text for roff, so dashes stay dashes and lines can't start a request.
+fn roff(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('-', "\\-");
+    if escaped.starts_with(['.', '\'']) {
+        format!("\\&{escaped}")
+    } else {
+        escaped
+    }
+}
@@ -1,9 +1,14 @@
-#![feature(trait_upcasting)]
+pub mod api;
+pub mod args;
 pub mod commands;
+pub mod completions;
 pub mod config;
 pub mod error;
 pub mod functions;
+pub mod help;
+pub mod logs;
+pub mod output;
 pub mod traits;
 
 mod command;
-pub use command::Command;
+pub use command::{is_hidden, Command, Parsed, Unknown};
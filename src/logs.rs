@@ -0,0 +1,291 @@
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{Mutex, OnceLock},
+};
+
+use chrono::Local;
+use simplelog::*;
+
+use crate::args::{raw_count, raw_option, Arg, GLOBAL};
+use crate::output::Format;
+
+/// Values of the global `--log-level` flag.
+pub const LEVELS: &[&str] = &["off", "error", "warn", "info", "debug", "trace"];
+
+/// Default of the `keep-logs` flag, how many run logs are kept including the current one.
+pub const KEEP: &str = "50";
+/// Older run logs are removed until the saved ones fit in this many bytes.
+const MAX_BYTES: u64 = 10 * 1024 * 1024;
+/// A run log stops here, so a long `fs` session can't fill the disk.
+const MAX_RUN_BYTES: u64 = 2 * 1024 * 1024;
+
+static CURRENT: OnceLock<PathBuf> = OnceLock::new();
+static SINK: Mutex<Sink> = Mutex::new(Sink::Pending {
+    buf: Vec::new(),
+    written: 0,
+});
+
+/// Where the run log goes, held in memory until the command is known.
+enum Sink {
+    Pending { buf: Vec<u8>, written: u64 },
+    File { file: File, written: u64 },
+    Discarded,
+}
+
+/// Writes to the run log of this run, see [`save`] and [`discard`].
+struct RunLog;
+
+impl Write for RunLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut *SINK.lock().unwrap() {
+            Sink::Pending {
+                buf: pending,
+                written,
+            } => append(pending, written, buf, MAX_RUN_BYTES)?,
+            Sink::File { file, written } => append(file, written, buf, MAX_RUN_BYTES)?,
+            Sink::Discarded => {}
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut *SINK.lock().unwrap() {
+            Sink::File { file, .. } => file.flush(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Writes `buf` unless `max` bytes were already written, noting where the log was cut.
+fn append(out: &mut impl Write, written: &mut u64, buf: &[u8], max: u64) -> io::Result<()> {
+    if *written >= max {
+        return Ok(());
+    }
+
+    *written += buf.len() as u64;
+    out.write_all(buf)?;
+    if *written >= max {
+        out.write_all(b"[log truncated, later lines are left out]\n")?;
+    }
+    Ok(())
+}
+
+/// Where run logs are saved, one file per run.
+pub fn dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap()
+        .join(env!("CARGO_PKG_NAME"))
+        .join("logs")
+}
+
+/// The level printed to the terminal, read from a command line before it is parsed.
+///
+/// `--log-level` wins over `--quiet`, which wins over `-v`.
+pub fn level_from_args(args: &[String]) -> LevelFilter {
+    if let Some(level) = raw_option(args, "log-level").and_then(|level| level.parse().ok()) {
+        return level;
+    }
+
+    if raw_count(args, global("quiet")) > 0 {
+        return LevelFilter::Error;
+    }
+
+    match raw_count(args, global("verbose")) {
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+fn global(name: &str) -> &'static Arg {
+    GLOBAL.iter().find(|arg| arg.name == name).unwrap()
+}
+
+/// Sets up logging for this run: `level` and up on the terminal, everything in the run log.
+///
+/// The run log is kept in memory until [`save`] or [`discard`] decides whether it gets a file.
+pub fn init(level: LevelFilter, format: Format) -> Result<(), Box<dyn Error>> {
+    CombinedLogger::init(vec![
+        TermLogger::new(
+            level,
+            Config::default(),
+            // stdout is kept for the json or yaml document
+            if format.is_text() {
+                TerminalMode::Mixed
+            } else {
+                TerminalMode::Stderr
+            },
+            ColorChoice::Auto,
+        ),
+        WriteLogger::new(LevelFilter::Trace, Config::default(), RunLog),
+    ])?;
+
+    Ok(())
+}
+
+/// Writes the run log to a new file, making room for it first so that `keep` logs are left.
+pub fn save(keep: usize) -> io::Result<()> {
+    let mut sink = SINK.lock().unwrap();
+    let Sink::Pending { buf, written } = &*sink else {
+        return Ok(());
+    };
+
+    let dir = dir();
+    fs::create_dir_all(&dir)?;
+    rotate(keep)?;
+    // left behind by versions that kept a single log
+    let _ = fs::remove_file(dir.with_file_name("latest.log"));
+
+    let path = dir.join(format!(
+        "{}-{}.log",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        process::id()
+    ));
+    let mut file = File::create(&path)?;
+    file.write_all(buf)?;
+    let _ = CURRENT.set(path);
+    *sink = Sink::File {
+        file,
+        written: *written,
+    };
+
+    Ok(())
+}
+
+/// Drops the run log, for runs not worth looking back at.
+pub fn discard() {
+    *SINK.lock().unwrap() = Sink::Discarded;
+}
+
+/// The log of this run, if logging is set up.
+pub fn current() -> Option<&'static PathBuf> {
+    CURRENT.get()
+}
+
+/// Saved run logs, oldest first, leaving out the current run.
+pub fn list() -> io::Result<Vec<PathBuf>> {
+    let mut logs = fs::read_dir(dir())?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .filter(|path| Some(path) != current())
+        .collect::<Vec<_>>();
+    // names start with the time of the run
+    logs.sort();
+    Ok(logs)
+}
+
+/// Removes the oldest logs, leaving room for a new one within `keep` and `MAX_BYTES`.
+fn rotate(keep: usize) -> io::Result<()> {
+    let logs = list()?
+        .into_iter()
+        .map(|path| {
+            let size = size(&path);
+            (path, size)
+        })
+        .collect::<Vec<_>>();
+
+    prune(&logs, keep, MAX_BYTES)
+        .into_iter()
+        .try_for_each(fs::remove_file)
+}
+
+/// The logs to remove, oldest first, so that fewer than `keep` are left within `max_bytes`.
+///
+/// `logs` are paths with their sizes, oldest first.
+fn prune(logs: &[(PathBuf, u64)], keep: usize, max_bytes: u64) -> Vec<&Path> {
+    let mut total = logs.iter().map(|(_, size)| size).sum::<u64>();
+    let mut left = logs.len();
+
+    logs.iter()
+        .take_while(|(_, size)| {
+            if left < keep && total <= max_bytes {
+                return false;
+            }
+            total -= size;
+            left -= 1;
+            true
+        })
+        .map(|(path, _)| path.as_path())
+        .collect()
+}
+
+fn size(path: &Path) -> u64 {
+    path.metadata().map(|meta| meta.len()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logs(sizes: &[u64]) -> Vec<(PathBuf, u64)> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, size)| (PathBuf::from(format!("{i}.log")), *size))
+            .collect()
+    }
+
+    fn names(pruned: Vec<&Path>) -> Vec<String> {
+        pruned
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn nothing_pruned_within_limits() {
+        assert!(prune(&logs(&[1, 1, 1]), 4, 10).is_empty());
+        assert!(prune(&[], 1, 0).is_empty());
+    }
+
+    #[test]
+    fn count_leaves_room_for_the_new_log() {
+        assert_eq!(names(prune(&logs(&[1, 1, 1]), 3, 10)), ["0.log"]);
+        assert_eq!(
+            names(prune(&logs(&[1, 1, 1, 1]), 2, 10)),
+            ["0.log", "1.log", "2.log"]
+        );
+    }
+
+    #[test]
+    fn size_removes_oldest_first() {
+        // the newest log is large, but only older ones go until the total fits
+        assert_eq!(
+            names(prune(&logs(&[4, 4, 4, 8]), 10, 12)),
+            ["0.log", "1.log"]
+        );
+        assert_eq!(
+            names(prune(&logs(&[4, 4, 4, 8]), 10, 5)),
+            ["0.log", "1.log", "2.log", "3.log"]
+        );
+    }
+
+    #[test]
+    fn run_log_stops_at_its_cap() {
+        let (mut out, mut written) = (Vec::new(), 0);
+        for line in ["one\n", "two\n", "three\n"] {
+            append(&mut out, &mut written, line.as_bytes(), 6).unwrap();
+        }
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "one\ntwo\n[log truncated, later lines are left out]\n"
+        );
+    }
+
+    #[test]
+    fn stricter_limit_wins() {
+        assert_eq!(
+            names(prune(&logs(&[1, 1, 1, 1]), 2, 100)),
+            ["0.log", "1.log", "2.log"]
+        );
+        assert_eq!(
+            names(prune(&logs(&[5, 5, 5, 5]), 10, 10)),
+            ["0.log", "1.log"]
+        );
+    }
+}
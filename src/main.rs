@@ -1,79 +1,238 @@
 use log::*;
-use std::{
-    collections::HashMap,
-    env,
-    error::Error,
-    fs::{self, OpenOptions},
-};
+use std::{collections::HashMap, env, error::Error, fs, process::ExitCode};
 
 use gm_cli::{
     commands,
-    config::{AccountConfig, ApplicationsConfig},
-    functions::args_parse,
-    traits::ConfigTriat,
+    config::{
+        apply_env, AccountConfig, AliasesConfig, ApplicationsConfig, DefaultsConfig,
+        InstancesConfig, Source, SOURCES,
+    },
+    error::{exit_code, Error as CError, EXIT_CONFIG, EXIT_USAGE},
+    functions::{disable_input, init_client},
+    help::{self, help},
+    is_hidden, logs,
+    output::{self, Format, Output},
+    traits::{config_dir, read_only, ConfigTriat},
 };
-use simplelog::*;
 
-fn main() {
+fn main() -> ExitCode {
     init().unwrap();
-    CombinedLogger::init(vec![
-        TermLogger::new(
-            LevelFilter::Info,
-            Config::default(),
-            TerminalMode::Mixed,
-            ColorChoice::Auto,
-        ),
-        WriteLogger::new(
-            LevelFilter::Trace,
-            Config::default(),
-            OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(
-                    dirs::data_dir()
-                        .unwrap()
-                        .join(env!("CARGO_PKG_NAME"))
-                        .join("latest.log"),
-                )
-                .unwrap(),
-        ),
-    ])
-    .unwrap();
-
-    let mut args = env::args().skip(1).collect::<Vec<_>>();
+
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let format = Format::from_args(&args).unwrap_or_else(configured_format);
+    format.init();
+    // shells run completions on every tab press, so they never prompt, print anything but
+    // candidates or rewrite config files
+    let completing = args.iter().any(|arg| arg.starts_with("__complete-"));
+    if completing || args.iter().any(|arg| arg == "--no-input") {
+        disable_input();
+    }
+    if completing {
+        read_only();
+    }
+
+    let level = if completing {
+        LevelFilter::Off
+    } else {
+        logs::level_from_args(&args)
+    };
+    logs::init(level, format).unwrap();
+
     debug!("Running with args {}", args.join(" "));
 
     if args.is_empty() {
+        save_log(&[], &HashMap::new());
         error!("No command found");
-        return;
+        return exit(EXIT_USAGE);
+    }
+
+    // aliases are needed to find the command, so they are loaded before the other files
+    if let Err(e) = AliasesConfig::load() {
+        error!("Failed to load aliases: {e}");
+        info!("Your aliases are ignored until `config edit aliases` fixes the file");
+    }
+
+    let commands = commands::commands();
+    if args.iter().any(|arg| arg == "--help") {
+        logs::discard();
+        let help = help(&commands, &args);
+        output::render(
+            &Output::new("Help")
+                .text(&help)
+                .field("help", help::plain(&help)),
+        );
+        return ExitCode::SUCCESS;
     }
 
+    let parsed = match commands.parse_or_suggest(&args) {
+        Some(parsed) => parsed,
+        None => {
+            save_log(&[], &HashMap::new());
+            return exit(EXIT_USAGE);
+        }
+    };
+
+    let accounts = match (needs_account(&parsed.path, parsed.spec.login), completing) {
+        (false, _) => Ok(None),
+        // without input encrypted tokens only unlock from the environment, otherwise there is no account
+        (true, true) => Ok(AccountConfig::load().ok()),
+        (true, false) => AccountConfig::load().map(Some),
+    };
+
     let mut args_map = HashMap::new();
 
-    if let Err(e) = config_init(&mut args_map) {
+    if let Err(e) = accounts
+        .and_then(|accounts| config_init(&mut args_map, &parsed.flags, &parsed.path, accounts))
+    {
         error!("Failed to load config: {e}");
-        info!("Try deleting the problematic config file to regenereate");
-        return;
+
+        if completing {
+            return ExitCode::SUCCESS;
+        }
+        save_log(&parsed.path, &parsed.flags);
+
+        // the config command is how broken files get fixed, so let it through
+        if parsed.path.first() != Some(&"config") {
+            info!(
+                "Try `config edit [name]`, or deleting the problematic config file to regenereate"
+            );
+            output::render_error(&*e, EXIT_CONFIG);
+            return exit(EXIT_CONFIG);
+        }
+        SOURCES.init(HashMap::new());
     };
-    args_parse(&mut args, &mut args_map);
+    parsed.flags.keys().for_each(|key| {
+        SOURCES.get_mut().insert(key.to_string(), Source::Flag);
+    });
+    args_map.extend(parsed.flags.clone());
+    if args_map
+        .get("output")
+        .is_some_and(|output| Format::from_name(output) != format)
+    {
+        warn!("An `output` default only applies under `global` in defaults.yml");
+    }
+    if args_map.contains_key("no-http") {
+        args_map.remove("http");
+        SOURCES.get_mut().insert("http".to_string(), Source::Flag);
+    }
+    // saved once the config is loaded, which may set `keep-logs`
+    save_log(&parsed.path, &args_map);
+
+    if let Err(e) = init_client(&args_map) {
+        error!("Failed to set up connection settings: {e}");
+        if completing {
+            return ExitCode::SUCCESS;
+        }
+        output::render_error(&*e, EXIT_CONFIG);
+        return exit(EXIT_CONFIG);
+    }
+
+    match parsed.run(args_map) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => exit(exit_code(&*e)),
+    }
+}
 
-    commands::commands().run(args_map, args);
+fn exit(code: i32) -> ExitCode {
+    ExitCode::from(code as u8)
 }
 
 fn init() -> Result<(), Box<dyn Error>> {
     let package = env!("CARGO_PKG_NAME");
 
-    fs::create_dir_all(dirs::config_dir().unwrap().join(package))?;
+    fs::create_dir_all(config_dir())?;
     fs::create_dir_all(dirs::data_dir().unwrap().join(package))?;
     fs::create_dir_all(dirs::cache_dir().unwrap().join(package).join("downloads"))?;
 
     Ok(())
 }
 
-fn config_init(map: &mut HashMap<String, String>) -> Result<(), Box<dyn Error>> {
-    AccountConfig::load()?.extend_map(map);
+/// The `output` default under `global` in defaults.yml, read before logging is set up
+/// so it can't come from instance or command defaults.
+fn configured_format() -> Format {
+    DefaultsConfig::read()
+        .ok()
+        .and_then(|defaults| {
+            defaults
+                .global
+                .get("output")?
+                .as_str()
+                .map(Format::from_name)
+        })
+        .unwrap_or(Format::Text)
+}
+
+/// Saves the run log, or drops it for runs `keeps_log` leaves out.
+fn save_log(command: &[&str], map: &HashMap<String, String>) {
+    if !keeps_log(command) {
+        logs::discard();
+        return;
+    }
+
+    let keep = map
+        .get("keep-logs")
+        .map(String::as_str)
+        .unwrap_or(logs::KEEP);
+    let keep = keep.parse().unwrap_or_else(|_| {
+        warn!("`keep-logs` must be a whole number, got `{keep}`");
+        logs::KEEP.parse().unwrap()
+    });
+    if let Err(e) = logs::save(keep) {
+        warn!("Failed to save the log of this run: {e}");
+    }
+}
+
+/// Whether a run is worth a run log, leaving out ones that only print help or read logs, and hidden ones run by shells.
+fn keeps_log(command: &[&str]) -> bool {
+    !matches!(
+        command,
+        ["completions" | "help" | "logs" | "man" | "version", ..]
+    ) && !command.iter().any(|name| is_hidden(name))
+}
+
+/// Whether a command uses the saved accounts, loading them may ask for the passphrase of encrypted tokens.
+fn needs_account(command: &[&str], login: bool) -> bool {
+    login || command.first() == Some(&"account") || command.last() == Some(&"__complete-path")
+}
+
+fn config_init(
+    map: &mut HashMap<String, String>,
+    flags: &HashMap<String, String>,
+    command: &[&str],
+    accounts: Option<AccountConfig>,
+) -> Result<(), Box<dyn Error>> {
+    let mut sources = HashMap::new();
+
+    if let Some(accounts) = accounts {
+        let account = match flags.get("account") {
+            Some(name) if !accounts.accounts.contains_key(name) => {
+                return Err(CError::Usage(format!("no saved account named `{name}`")).into())
+            }
+            Some(name) => accounts.account_map(name),
+            None => accounts.to_map(),
+        };
+        account.keys().for_each(|key| {
+            sources.insert(key.to_string(), Source::File(AccountConfig::path()));
+        });
+        map.extend(account);
+    }
     ApplicationsConfig::load()?;
+    InstancesConfig::load()?;
+
+    sources.extend(apply_env(map));
+    sources.retain(|key, _| map.contains_key(key));
+
+    let instance = flags.get("instance").or(map.get("instance"));
+    let defaults = DefaultsConfig::load()?.resolve(instance.map(String::as_str), command);
+    for (key, value) in defaults {
+        if map.contains_key(&key) || flags.contains_key(&key) {
+            continue;
+        }
+        sources.insert(key.clone(), Source::File(DefaultsConfig::path()));
+        map.insert(key, value);
+    }
+
+    SOURCES.init(sources);
 
     Ok(())
 }
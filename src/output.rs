@@ -0,0 +1,184 @@
+use std::{error::Error as StdError, fmt::Display, sync::OnceLock};
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::args::raw_option;
+use crate::error::Error as CError;
+use crate::Unknown;
+
+/// Values of the global `--output` flag.
+pub const FORMATS: &[&str] = &["text", "json", "yaml"];
+
+static FORMAT: OnceLock<Format> = OnceLock::new();
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    /// Human readable text on stdout, with logs mixed in.
+    Text,
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// Finds `--output` in a command line before it is parsed, so logging can be set up first.
+    /// Invalid values are left for the dispatcher to report.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        raw_option(args, "output").map(Self::from_name)
+    }
+
+    /// A value of `--output`, anything unknown is text.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "json" => Self::Json,
+            "yaml" => Self::Yaml,
+            _ => Self::Text,
+        }
+    }
+
+    /// Sets the format for the rest of the run, only the first call has an effect.
+    pub fn init(self) {
+        let _ = FORMAT.set(self);
+    }
+
+    pub fn get() -> Self {
+        FORMAT.get().copied().unwrap_or(Self::Text)
+    }
+
+    pub fn is_text(self) -> bool {
+        self == Self::Text
+    }
+}
+
+/// What a command returns.
+///
+/// `text` is printed as is by default, while `--output json|yaml` prints `value` instead.
+#[derive(Default)]
+pub struct Output {
+    /// Short summary, such as `Copied`.
+    pub message: String,
+    pub text: Option<String>,
+    pub value: Value,
+}
+
+impl Output {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn text(self, text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..self
+        }
+    }
+
+    /// Sets the whole result, keeping the field names of `value`.
+    pub fn value(self, value: impl Serialize) -> Self {
+        Self {
+            value: serde_json::to_value(value).unwrap_or_default(),
+            ..self
+        }
+    }
+
+    /// Adds one field to the result.
+    pub fn field(mut self, key: &str, value: impl Serialize) -> Self {
+        if !self.value.is_object() {
+            self.value = Value::Object(Map::new());
+        }
+        self.value.as_object_mut().unwrap().insert(
+            key.to_string(),
+            serde_json::to_value(value).unwrap_or_default(),
+        );
+        self
+    }
+}
+
+/// Prints a line right away in text mode, for commands that show parts of the result as they go.
+pub fn print_text(text: impl Display) {
+    if Format::get().is_text() {
+        println!("{text}");
+    }
+}
+
+/// Prints the result of a command in the selected format.
+pub fn render(output: &Output) {
+    match Format::get() {
+        Format::Text => match &output.text {
+            Some(text) if !text.is_empty() => println!("{text}"),
+            _ => {}
+        },
+        format => print(
+            format,
+            json!({
+                "status": "ok",
+                "message": output.message,
+                "result": output.value,
+            }),
+        ),
+    }
+}
+
+/// Prints an error as a document when the output is structured, text output only logs errors.
+/// `code` is what the process exits with.
+pub fn render_error(e: &(dyn StdError + 'static), code: i32) {
+    let format = Format::get();
+    if format.is_text() {
+        return;
+    }
+
+    let mut error = json!({
+        "kind": "other",
+        "message": e.to_string(),
+        "code": code,
+    });
+    if let Some(e) = e.downcast_ref::<CError>() {
+        let fields = error.as_object_mut().unwrap();
+        fields.insert("kind".to_string(), json!(e.kind()));
+        match e {
+            CError::Server { endpoint, kind } => {
+                fields.insert("endpoint".to_string(), json!(endpoint));
+                fields.insert(
+                    "server_error".to_string(),
+                    serde_json::to_value(kind).unwrap_or_default(),
+                );
+            }
+            CError::Network { endpoint, .. } | CError::Response { endpoint, .. } => {
+                fields.insert("endpoint".to_string(), json!(endpoint));
+            }
+            CError::Io { path, .. } | CError::Config { path, .. } => {
+                fields.insert("path".to_string(), json!(path));
+            }
+            CError::Checksum { path, .. } => {
+                fields.insert("path".to_string(), json!(path));
+            }
+            _ => {}
+        }
+        if let Some(remedy) = e.remedy() {
+            fields.insert("remedy".to_string(), json!(remedy));
+        }
+    } else if let Some(unknown) = e.downcast_ref::<Unknown>() {
+        let fields = error.as_object_mut().unwrap();
+        fields.insert("kind".to_string(), json!("usage"));
+        fields.insert("suggestions".to_string(), json!(unknown.suggestions));
+    }
+
+    print(
+        format,
+        json!({
+            "status": "error",
+            "error": error,
+        }),
+    );
+}
+
+fn print(format: Format, document: Value) {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(&document).unwrap()),
+        Format::Yaml => print!("{}", serde_yaml::to_string(&document).unwrap()),
+        Format::Text => unreachable!(),
+    }
+}
@@ -4,13 +4,23 @@ use std::{
     error::Error,
     fs::{self, OpenOptions},
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
 use log::*;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_yaml::Value;
 
+use crate::error::Error as CError;
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Stops config files from being written for the rest of the run.
+pub fn read_only() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
+
 pub trait ConfigTriat
 where
     Self: Serialize + DeserializeOwned + Clone + Default,
@@ -18,13 +28,11 @@ where
     const NAME: &'static str;
 
     fn path() -> PathBuf {
-        dirs::config_dir()
-            .unwrap()
-            .join(env!("CARGO_PKG_NAME"))
-            .join(format!("{}.yml", Self::NAME))
+        config_dir().join(format!("{}.yml", Self::NAME))
     }
 
-    fn load() -> Result<Self, Box<dyn Error>> {
+    /// Reads the config file without writing it back, falls back to default if missing.
+    fn read() -> Result<Self, Box<dyn Error>> {
         let path = Self::path();
 
         debug!("Reading config file at {:?}", Self::path());
@@ -32,34 +40,39 @@ where
         let config = if path.exists() {
             let s = match fs::read_to_string(&path) {
                 Ok(s) => s,
-                Err(e) => {
-                    return Err(crate::error::Error::StringErr(format!(
-                        "unable to read config file at `{path:?}`: {e}",
-                    ))
-                    .into())
-                }
+                Err(e) => return Err(CError::io(&path, e).into()),
             };
 
             match serde_yaml::from_str(&s) {
                 Ok(v) => v,
-                Err(e) => {
-                    return Err(crate::error::Error::StringErr(format!(
-                        "error parsing config file at `{path:?}`: {e}"
-                    ))
-                    .into())
-                }
+                Err(e) => return Err(CError::config(path, e).into()),
             }
         } else {
             info!("No config file found at {:?}, using default", Self::path());
             Self::default()
         };
 
+        Ok(config)
+    }
+
+    fn load() -> Result<Self, Box<dyn Error>> {
+        let config = Self::read()?;
+
         debug!("Saving config file after load to {:?}", Self::path());
         config.save()?;
         Ok(config)
     }
 
     fn save(&self) -> Result<(), Box<dyn Error>> {
+        self.write()
+    }
+
+    /// Writes the config as is, `save` may be overridden to transform it first.
+    fn write(&self) -> Result<(), Box<dyn Error>> {
+        if is_read_only(&Self::path()) {
+            return Ok(());
+        }
+
         let s = serde_yaml::to_string(&self).unwrap();
         let mut file = OpenOptions::new()
             .write(true)
@@ -71,6 +84,48 @@ where
         Ok(())
     }
 
+    /// The config file as raw YAML, without going through `load`.
+    fn read_value() -> Result<Value, Box<dyn Error>> {
+        let path = Self::path();
+
+        if !path.exists() {
+            return Ok(serde_yaml::to_value(Self::default())?);
+        }
+
+        let s = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) => return Err(CError::io(&path, e).into()),
+        };
+
+        match serde_yaml::from_str(&s) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(CError::config(path, e).into()),
+        }
+    }
+
+    /// Checks that a raw YAML value would load as this config, without keys it would drop.
+    fn validate(value: &Value) -> Result<(), Box<dyn Error>> {
+        let config = match serde_yaml::from_value::<Self>(value.clone()) {
+            Ok(config) => config,
+            Err(e) => return Err(CError::config(Self::path(), e).into()),
+        };
+
+        match unknown_key(value, &serde_yaml::to_value(config)?) {
+            Some(key) => Err(CError::config(Self::path(), format!("unknown key `{key}`")).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates and writes a raw YAML value to the config file.
+    fn write_value(value: &Value) -> Result<(), Box<dyn Error>> {
+        Self::validate(value)?;
+        if is_read_only(&Self::path()) {
+            return Ok(());
+        }
+        fs::write(Self::path(), serde_yaml::to_string(value)?)?;
+        Ok(())
+    }
+
     fn clear() -> Result<(), Box<dyn Error>> {
         Self::default().save()
     }
@@ -90,6 +145,52 @@ where
     }
 }
 
+/// The first key in `given` missing from `known`, as a dotted path.
+///
+/// `known` is `given` loaded and written back, so anything it lacks would be silently dropped.
+/// Keys set to null are left out, they may stand for a missing optional value.
+fn unknown_key(given: &Value, known: &Value) -> Option<String> {
+    let (Value::Mapping(given), Value::Mapping(known)) = (given, known) else {
+        return None;
+    };
+
+    given.iter().find_map(|(key, value)| {
+        let name = match key {
+            Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => {
+                return Some(
+                    serde_yaml::to_string(key)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string(),
+                )
+            }
+            key => convert_value_to_string(key),
+        };
+        // keys such as `8080` are read as numbers but may load as strings
+        match known.get(key).or_else(|| known.get(name.as_str())) {
+            Some(known) => unknown_key(value, known).map(|inner| format!("{name}.{inner}")),
+            None if value.is_null() => None,
+            None => Some(name),
+        }
+    })
+}
+
+fn is_read_only(path: &Path) -> bool {
+    let read_only = READ_ONLY.load(Ordering::Relaxed);
+    if read_only {
+        debug!("Not writing config file at {path:?}, config is read only for this run");
+    }
+    read_only
+}
+
+/// The config directory, `GM_CONFIG_DIR` takes priority over the platform default.
+pub fn config_dir() -> PathBuf {
+    match env::var_os(crate::config::CONFIG_DIR_ENV) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::config_dir().unwrap().join(env!("CARGO_PKG_NAME")),
+    }
+}
+
 fn convert_value_to_string(value: &Value) -> String {
     match value {
         Value::Null => String::new(),
@@ -106,3 +207,34 @@ fn convert_hashmap_to_string(hashmap: HashMap<String, Value>) -> HashMap<String,
         .map(|(k, v)| (k, convert_value_to_string(&v)))
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(s: &str) -> Value {
+        serde_yaml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn unknown_keys_are_found_at_any_depth() {
+        let known = yaml("a: 1\nb:\n  c: 2");
+        assert_eq!(unknown_key(&yaml("a: 1\nb:\n  c: 2"), &known), None);
+        assert_eq!(
+            unknown_key(&yaml("a: 1\nd: 3"), &known),
+            Some("d".to_string())
+        );
+        assert_eq!(
+            unknown_key(&yaml("b:\n  e: 2"), &known),
+            Some("b.e".to_string())
+        );
+    }
+
+    #[test]
+    fn null_keys_are_not_unknown() {
+        assert_eq!(
+            unknown_key(&yaml("a: 1\nencryption: null"), &yaml("a: 1")),
+            None
+        );
+    }
+}
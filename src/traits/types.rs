@@ -1,4 +1,6 @@
-use std::{collections::HashMap, error::Error};
+use std::error::Error;
 
-pub type CommandFnType =
-    Box<dyn Fn(HashMap<String, String>, Vec<String>) -> Result<String, Box<dyn Error>>>;
+use crate::args::Args;
+use crate::output::Output;
+
+pub type CommandFnType = fn(Args) -> Result<Output, Box<dyn Error>>;